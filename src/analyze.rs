@@ -1,13 +1,37 @@
 use std::io::{self, Write};
 use std::rc::Rc;
 
+use chrono::NaiveDate;
 use instant::{Duration, Instant};
-use web_sys::HtmlCanvasElement;
+use pulldown_cmark::{Options, Parser, html};
 
-use crate::options::{AnalysisOption, StatsType};
+use crate::chart::{Chart, ChartCanvas, ChartImage, TrendScale};
+use crate::options::{
+    AnalysisOption, CalendarUnit, Percentile, StatsType, TargetRange, WarmupScope,
+};
+use crate::parser::ParseDiagnostic;
 use crate::record::Record;
-use crate::session::Session;
-use crate::time::{AsSeconds, HumanReadable};
+use crate::session::{HeatmapRow, Session, TrendPoint};
+use crate::significance::{ALPHA, mann_whitney_u, welch_t_test};
+use crate::stats::Dispersion;
+use crate::time::{AsSeconds, Milliseconds, Seconds};
+
+/// The count of stats in a window of sub-X rate trends.
+const SUBX_WINDOW: usize = 100;
+
+/// The least count of sittings or days reaching
+/// a position, for the position to be shown in warm-up.
+const WARMUP_MIN_PARTS: usize = 5;
+
+/// The least count of solves in an hour of a weekday,
+/// for its mean to be shown in time-of-day heatmaps.
+const TIMEOFDAY_MIN_COUNT: usize = 5;
+
+/// A value taken from a `Record`, in place of its time.
+type Metric = fn(&Record) -> Option<Milliseconds>;
+
+/// Formats a value for chart labels.
+type Formatter = fn(&Seconds) -> String;
 
 /// Calculates a percentage.
 fn percentage(count: usize, total: usize) -> f32 {
@@ -20,7 +44,7 @@ fn percentage(count: usize, total: usize) -> f32 {
 
 /// Returns the letter 's' for a plural form
 /// in en-us, or nothing for a single form.
-fn plural_form(count: usize) -> String {
+pub(crate) fn plural_form(count: usize) -> String {
     if count <= 1 {
         String::new()
     } else {
@@ -28,6 +52,18 @@ fn plural_form(count: usize) -> String {
     }
 }
 
+/// Converts the markdown content to HTML.
+pub fn markdown_to_html(input: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+
+    let parser = Parser::new_ext(input, options);
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+
+    html_output
+}
+
 /// Writes a markdown heading with the specified level.
 fn write_heading<W: Write>(writer: &mut W, level: usize, title: &str) -> io::Result<()> {
     writeln!(writer, "{} {}\n", "#".repeat(level), title)
@@ -37,10 +73,15 @@ fn write_heading<W: Write>(writer: &mut W, level: usize, title: &str) -> io::Res
 pub fn write_analysis_info<W: Write>(
     writer: &mut W,
     sessions: &[Session],
+    diagnostics: &[ParseDiagnostic],
     options: &[AnalysisOption],
 ) -> io::Result<bool> {
     write_heading(writer, 3, &t!("title.dataset"))?;
 
+    if !diagnostics.is_empty() {
+        write_diagnostics(writer, diagnostics)?;
+    }
+
     if sessions.is_empty() {
         let info = t!("info.no-session-parsed");
         writeln!(writer, "{}\n", info)?;
@@ -65,21 +106,8 @@ pub fn write_analysis_info<W: Write>(
     );
     writeln!(writer, "{}\n", session_info)?;
 
-    for session in sessions {
-        writeln!(
-            writer,
-            "- [[#{}] **{}** (`{}` {})](#session{})",
-            session.rank(),
-            session.name(),
-            session.record_count(),
-            t!(
-                "record.plural",
-                record_count_plural = plural_form(record_count)
-            ),
-            session.rank(),
-        )?;
-    }
-    writeln!(writer)?;
+    let dnfasok = options.contains(&AnalysisOption::DnfAsOk);
+    write_overview_table(writer, sessions, dnfasok)?;
 
     write_heading(writer, 3, &t!("title.analysis-options"))?;
 
@@ -105,6 +133,107 @@ pub fn write_analysis_info<W: Write>(
     Ok(false)
 }
 
+/// Writes a table with a row of key stats for every session,
+/// linked to the section of the session.
+fn write_overview_table<W: Write>(
+    writer: &mut W,
+    sessions: &[Session],
+    dnfasok: bool,
+) -> io::Result<()> {
+    const BEST_TYPES: [StatsType; 4] = [
+        StatsType::Single,
+        StatsType::Average(5),
+        StatsType::Average(12),
+        StatsType::Average(100),
+    ];
+    const CURRENT_TYPES: [StatsType; 2] = [StatsType::Average(5), StatsType::Average(12)];
+
+    let best_headers = BEST_TYPES
+        .iter()
+        .map(|s_type| format!("{} {}", t!("stats.best"), s_type));
+    let current_headers = CURRENT_TYPES
+        .iter()
+        .map(|s_type| t!("stats.current", s_type = s_type).to_string());
+    let headers: Vec<String> = [
+        t!("stats.session").to_string(),
+        t!("stats.event").to_string(),
+        t!("stats.solves").to_string(),
+        t!("stats.days-practiced").to_string(),
+    ]
+    .into_iter()
+    .chain(best_headers)
+    .chain(current_headers)
+    .chain(std::iter::once(t!("stats.last-practice").to_string()))
+    .collect();
+
+    writeln!(writer, "| {} |", headers.join(" | "))?;
+    writeln!(writer, "|{}", " :-: |".repeat(headers.len()))?;
+
+    for session in sessions {
+        let format = |stats: Option<Milliseconds>, s_type: &StatsType| match stats {
+            Some(t) => format!("`{}`", session.format_time(t)),
+            None if session.record_count() < s_type.scale() => String::from("-"),
+            None => String::from("`DNF`"),
+        };
+        let best = BEST_TYPES
+            .iter()
+            .map(|s_type| format(session.best_stats(s_type, dnfasok), s_type));
+        let current = CURRENT_TYPES
+            .iter()
+            .map(|s_type| format(session.current_stats(s_type, dnfasok), s_type));
+        let last_practice = session
+            .records()
+            .last()
+            .map_or_else(String::new, |r| r.date_time().date_naive().to_string());
+
+        let cells: Vec<String> = [
+            format!(
+                "[[#{}] **{}**](#session{})",
+                session.rank(),
+                session.name(),
+                session.rank()
+            ),
+            session.puzzle().to_string(),
+            format!("`{}`", session.record_count()),
+            format!("`{}`", session.days_with_record()),
+        ]
+        .into_iter()
+        .chain(best)
+        .chain(current)
+        .chain(std::iter::once(last_practice))
+        .collect();
+
+        writeln!(writer, "| {} |", cells.join(" | "))?;
+    }
+
+    writeln!(writer)
+}
+
+/// Writes the problems found while parsing the data,
+/// in a HTML collapsible element.
+fn write_diagnostics<W: Write>(writer: &mut W, diagnostics: &[ParseDiagnostic]) -> io::Result<()> {
+    let count = diagnostics.len();
+    write_message(
+        writer,
+        &t!("label.warning"),
+        &t!(
+            "info.parse-diagnostics",
+            count = count,
+            count_plural = plural_form(count)
+        ),
+    )?;
+
+    writeln!(
+        writer,
+        "<details>\n<summary>{}</summary>\n",
+        t!("info.parse-diagnostics-detail")
+    )?;
+    for diagnostic in diagnostics {
+        writeln!(writer, "- {}", diagnostic)?;
+    }
+    writeln!(writer, "\n</details>\n")
+}
+
 /// Writes information about days practiced on a session.
 fn write_session_date_time<W: Write>(writer: &mut W, session: &Session) -> io::Result<()> {
     let (start, end) = session.date_time();
@@ -130,13 +259,25 @@ fn write_session_date_time<W: Write>(writer: &mut W, session: &Session) -> io::R
     writeln!(writer, "- {}\n- {}\n", t_days_total, t_days_practiced)
 }
 
+/// Formats the signed difference from a time to another.
+fn format_difference(session: &Session, from: Milliseconds, to: Milliseconds) -> String {
+    let sign = if to < from { '-' } else { '+' };
+    format!("{}{}", sign, session.format_time(to.abs_diff(from)))
+}
+
 /// Writes the details of some `Record`s, a HTML collapsible
 /// element will be added when there are more than one `Record`.
 fn write_records_detail<W: Write>(
     writer: &mut W,
+    session: &Session,
     records: &[(usize, Rc<Record>)],
 ) -> io::Result<()> {
-    writeln!(writer, "[#{}] {}", records[0].0, records[0].1)?;
+    writeln!(
+        writer,
+        "[#{}] {}",
+        records[0].0,
+        session.record_detail(&records[0].1)
+    )?;
 
     if records.len() > 1 {
         writeln!(
@@ -145,7 +286,7 @@ fn write_records_detail<W: Write>(
             t!("session.more-records")
         )?;
         for pair in records.iter().skip(1) {
-            writeln!(writer, "[#{}] {}", pair.0, pair.1)?;
+            writeln!(writer, "[#{}] {}", pair.0, session.record_detail(&pair.1))?;
         }
         writeln!(writer, "</details>\n")?;
     }
@@ -159,19 +300,29 @@ fn write_summary_table<W: Write>(
     session: &Session,
     dnfasok: bool,
 ) -> io::Result<()> {
-    let (best, worst, mean, average) = session.summary(dnfasok);
+    let (best, worst, mean, average, dispersion) = session.summary(dnfasok);
+    let puzzle = session.puzzle();
     let summary = format!(
         r"| {} | {} | {} | {} |
 | :-: | :-: | :-: | :-: |
-| `{}` | `{}` | `{}` | `{}` |",
+| `{}` | `{}` | `{}` | {} |",
         t!("stats.best"),
         t!("stats.worst"),
         t!("stats.mean"),
-        t!("stats.average"),
-        best.to_readable_string(),
-        worst.to_readable_string(),
-        mean.to_readable_string(),
-        average.map_or_else(|| String::from("DNF"), |avg| avg.to_readable_string())
+        if puzzle.is_blindfolded() {
+            t!("stats.best-mo3")
+        } else {
+            t!("stats.average")
+        },
+        session.format_time(best),
+        session.format_time(worst),
+        session.format_time(mean),
+        match average {
+            Some(avg) => format!("`{}`", session.format_time(avg)),
+            // BLD sessions of less than 3 solves have no mo3.
+            None if puzzle.is_blindfolded() && session.record_count() < 3 => String::from("-"),
+            None => String::from("`DNF`"),
+        }
     );
 
     let (ok, plus2, dnf) = session.solve_states();
@@ -187,7 +338,40 @@ fn write_summary_table<W: Write>(
         percentage(dnf, record_count),
     );
 
-    writeln!(writer, "{}\n\n{}\n", summary, solve_states)
+    writeln!(writer, "{}\n\n{}\n", summary, solve_states)?;
+
+    if let Some((sd, cv, iqr, mad)) = dispersion {
+        writeln!(
+            writer,
+            r"| {} | {} | {} | {} |
+| :-: | :-: | :-: | :-: |
+| `{}` | `{:.2}%` | `{}` | `{}` |
+",
+            t!("stats.sd"),
+            t!("stats.cv"),
+            t!("stats.iqr"),
+            t!("stats.mad"),
+            session.format_time(sd),
+            cv,
+            session.format_time(iqr),
+            session.format_time(mad),
+        )?;
+    }
+
+    if puzzle.is_blindfolded() {
+        let cs = t!("colon-space");
+        let success = record_count - dnf;
+        writeln!(
+            writer,
+            "- {}{cs}`{}/{}` `({:.2}%)`\n",
+            t!("stats.success-rate"),
+            success,
+            record_count,
+            percentage(success, record_count),
+        )?;
+    }
+
+    Ok(())
 }
 
 /// Writes a quote with a label and a message.
@@ -196,18 +380,32 @@ fn write_message<W: Write>(writer: &mut W, label: &str, content: &str) -> io::Re
     writeln!(writer, "> **{}**{cs}{}\n", label, content)
 }
 
-/// Writes an image data url.
-fn write_image_data_url<W: Write>(
-    writer: &mut W,
-    canvas: &HtmlCanvasElement,
-    desc: &str,
-) -> io::Result<()> {
-    writeln!(
-        writer,
-        "![{}]({})\n",
-        desc,
-        canvas.to_data_url().unwrap_or_default()
-    )
+/// Writes an image, either by its link or inline,
+/// blank lines are removed from inline SVG documents
+/// to keep them in a single markdown HTML block.
+fn write_image<W: Write>(writer: &mut W, image: &ChartImage, desc: &str) -> io::Result<()> {
+    match image {
+        ChartImage::Link(link) => writeln!(writer, "![{}]({})\n", desc, link),
+        ChartImage::Svg(svg) => {
+            writeln!(
+                writer,
+                "<div class=\"chart\" title=\"{}\">",
+                escape_attribute(desc.trim())
+            )?;
+            for line in svg.lines().filter(|l| !l.trim().is_empty()) {
+                writeln!(writer, "{}", line)?;
+            }
+            writeln!(writer, "</div>\n")
+        }
+    }
+}
+
+/// Escapes a text to be put in a double-quoted HTML attribute.
+fn escape_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
 }
 
 /// Writes debug information about analysis timings.
@@ -246,55 +444,35 @@ pub fn write_timings<W: Write>(
     Ok(())
 }
 
-/// Writes an analysis section.
-fn write_section<W: Write>(
+/// Writes the PB histories of some stats type.
+fn write_pbs<W: Write>(
     writer: &mut W,
     session: &Session,
-    op: &AnalysisOption,
+    s_type: &StatsType,
     dnfasok: bool,
-    canvas: &HtmlCanvasElement,
+    canvas: &mut dyn ChartCanvas,
 ) -> io::Result<()> {
-    write_heading(writer, 4, &format!("{}", op))?;
+    let pbs = session.pbs(s_type, dnfasok);
 
-    if let Some(s_type) = op.stats_type() {
-        let s_scale = s_type.scale();
-        if session.record_count() < s_scale {
-            return write_message(
-                writer,
-                &t!("label.info"),
-                &t!("info.records-not-enough", s_type = s_type),
-            );
-        }
+    if pbs.is_empty() {
+        return write_message(
+            writer,
+            &t!("label.info"),
+            &t!("info.no-pb-history", s_type = s_type),
+        );
     }
 
-    match op {
-        AnalysisOption::Summary => write_summary_table(writer, session, dnfasok),
-
-        // DEBUG ONLY
-        AnalysisOption::DnfAsOk => unreachable!(),
-
-        AnalysisOption::Pbs(s_type) => {
-            let pbs = session.pbs(s_type, dnfasok);
-
-            if pbs.is_empty() {
-                return write_message(
-                    writer,
-                    &t!("label.info"),
-                    &t!("info.no-pb-history", s_type = s_type),
-                );
-            }
-
-            let (first_pb, last_pb) = (pbs[0].1, pbs[pbs.len() - 1].1);
-            let pb_count = pbs.len();
-            let pbs_desc = pbs
-                .iter()
-                .map(|pair| pair.1.to_readable_string())
-                .collect::<Vec<_>>()
-                .join(" -> ");
+    let (first_pb, last_pb) = (pbs[0].1, pbs[pbs.len() - 1].1);
+    let pb_count = pbs.len();
+    let pbs_desc = pbs
+        .iter()
+        .map(|pair| session.format_time(pair.1))
+        .collect::<Vec<_>>()
+        .join(" -> ");
 
-            writeln!(
-                writer,
-                r"<details>
+    writeln!(
+        writer,
+        r"<details>
 <summary><code>{} -> {}</code> {}</summary>
 
 ```
@@ -303,120 +481,1470 @@ fn write_section<W: Write>(
 
 </details>
 ",
-                first_pb.to_readable_string(),
-                last_pb.to_readable_string(),
-                t!(
-                    "stats.pbs",
-                    pb_count = pb_count,
-                    pb_count_plural = plural_form(pb_count),
-                ),
-                pbs_desc,
-            )?;
+        session.format_time(first_pb),
+        session.format_time(last_pb),
+        t!(
+            "stats.pbs",
+            pb_count = pb_count,
+            pb_count_plural = plural_form(pb_count),
+        ),
+        pbs_desc,
+    )?;
 
-            if pb_count > 1 {
-                let cs = t!("colon-space");
-                let trends = session.pbs_trends(&pbs);
-                let desc = format!("{}{cs}{} {}", session, s_type, t!("stats.pbs-desc"));
-                match session.draw_trending(canvas, &trends, &desc) {
-                    Ok(()) => write_image_data_url(writer, canvas, &desc)?,
-                    Err(e) => write_message(
-                        writer,
-                        &t!("label.error"),
-                        &t!("error.trending-chart-fail", error_info = e),
-                    )?,
-                }
+    if pb_count > 1 {
+        let cs = t!("colon-space");
+        let trends = session.pbs_trends(&pbs);
+        let desc = format!("{}{cs}{} {}", session, s_type, t!("stats.pbs-desc"));
+        let chart = Chart::Trending {
+            trends: &trends,
+            x_desc: &t!("chart.trend-x-desc"),
+            y_scale: TrendScale::Time(&|v| session.puzzle().format_seconds(v)),
+        };
+        match canvas.draw(session, &chart, &desc) {
+            Ok(image) => write_image(writer, &image, &desc)?,
+            Err(e) => write_message(
+                writer,
+                &t!("label.error"),
+                &t!("error.trending-chart-fail", error_info = e),
+            )?,
+        }
+    }
+
+    if matches!(s_type, StatsType::Single) {
+        write_records_detail(
+            writer,
+            session,
+            &pbs.iter()
+                .map(|r| (r.0 + 1, r.2.clone()))
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        write_window_detail(writer, session, &pbs[0], s_type, dnfasok)?;
+
+        if pb_count > 1 {
+            writeln!(
+                writer,
+                "<details>\n<summary>... {}</summary>\n",
+                t!("session.more-records")
+            )?;
+            for pb in pbs.iter().skip(1) {
+                write_window_detail(writer, session, pb, s_type, dnfasok)?;
             }
+            writeln!(writer, "</details>\n")?;
+        }
 
-            if matches!(s_type, StatsType::Single) {
-                write_records_detail(
-                    writer,
-                    &pbs.iter()
-                        .map(|r| (r.0 + 1, r.2.clone()))
-                        .collect::<Vec<_>>(),
+        Ok(())
+    }
+}
+
+/// Writes the solves of the window of a PB, where trimmed
+/// solves are in parentheses, followed by their `Record`s.
+fn write_window_detail<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    pb: &(usize, Milliseconds, Rc<Record>),
+    s_type: &StatsType,
+    dnfasok: bool,
+) -> io::Result<()> {
+    let window = session.stats_window(pb.0, s_type, dnfasok);
+    let solves = window
+        .iter()
+        .map(|(_, r, trimmed)| {
+            let state = r.solve_state();
+            let time = if state.is_dnf() {
+                String::from("DNF")
+            } else if state.is_plus2() {
+                format!(
+                    "{}+",
+                    session.format_time(session.rounding().single(r.time()))
                 )
             } else {
-                Ok(())
-            }
-        }
+                session.format_time(session.rounding().single(r.time()))
+            };
 
-        AnalysisOption::Group(s_type, interval) => {
-            let mut interval = *interval;
-            if interval == 0 {
-                interval = session.decide_interval();
+            if *trimmed {
+                format!("({})", time)
+            } else {
+                time
             }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
 
-            let groups = session.group(interval, s_type, dnfasok);
+    writeln!(
+        writer,
+        "[#{}-#{}] `{}` @{}{}`{}`\n\n<details>\n<summary>{}</summary>\n",
+        window[0].0,
+        pb.0 + 1,
+        session.format_time(pb.1),
+        pb.2.date_time()
+            .to_string()
+            .strip_suffix(" UTC")
+            .unwrap_or_default(),
+        t!("colon-space"),
+        solves,
+        t!("session.window-records"),
+    )?;
+    for (i, record, _) in &window {
+        writeln!(writer, "[#{}] {}", i, session.record_detail(record))?;
+    }
 
-            let cs = t!("colon-space");
-            let desc = format!(
-                "{}{cs}{} {} {}",
-                session,
-                s_type,
-                t!("stats.groups"),
-                t!("stats.groups-interval", interval = interval.as_seconds()),
-            );
+    writeln!(writer, "</details>\n")
+}
 
-            match session.draw_grouping(canvas, &groups, interval, &desc) {
-                Ok(()) => write_image_data_url(writer, canvas, &desc),
-                Err(e) => write_message(
-                    writer,
-                    &t!("label.error"),
-                    &t!("error.grouping-chart-fail", error_info = e),
-                ),
+/// Writes the trend of some stats type,
+/// labeled by the specified formatter.
+fn write_trend<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    s_type: &StatsType,
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+    y_formatter: &dyn Fn(&Seconds) -> String,
+) -> io::Result<()> {
+    let trends = session.trend(s_type, dnfasok);
+
+    if trends.iter().all(|p| p.1.is_none()) {
+        return write_message(writer, &t!("label.info"), &t!("info.all-dnf"));
+    }
+
+    let cs = t!("colon-space");
+    let desc = format!("{}{cs}{} {}", session, s_type, t!("stats.trends"));
+
+    write_trending_chart(
+        writer,
+        session,
+        &trends,
+        &desc,
+        &t!("chart.trend-x-desc"),
+        true,
+        canvas,
+        TrendScale::Time(y_formatter),
+    )
+}
+
+/// Writes a trending chart of the values, labeled by the
+/// specified formatter, with tips on empty points if specified.
+#[allow(clippy::too_many_arguments)]
+fn write_trending_chart<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    trends: &[TrendPoint],
+    desc: &str,
+    x_desc: &str,
+    with_tips: bool,
+    canvas: &mut dyn ChartCanvas,
+    y_scale: TrendScale,
+) -> io::Result<()> {
+    let chart = Chart::Trending {
+        trends,
+        x_desc,
+        y_scale,
+    };
+    match canvas.draw(session, &chart, desc) {
+        Ok(image) => {
+            write_image(writer, &image, desc)?;
+            if with_tips {
+                write_message(writer, &t!("label.tips"), &t!("info.empty-points"))?;
             }
+            Ok(())
         }
+        Err(e) => write_message(
+            writer,
+            &t!("label.error"),
+            &t!("error.trending-chart-fail", error_info = e),
+        ),
+    }
+}
 
-        AnalysisOption::Trend(s_type) => {
-            let trends = session.trend(s_type, dnfasok);
+/// Writes a table of some percentiles of some stats type, over
+/// the session and the ranges of `Recent` options.
+fn write_percentiles<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    s_type: &StatsType,
+    percentiles: &[Percentile],
+    dnfasok: bool,
+    options: &[AnalysisOption],
+) -> io::Result<()> {
+    let format = |stats: Option<Milliseconds>| {
+        stats.map_or_else(|| String::from("DNF"), |t| session.format_time(t))
+    };
 
-            if trends.iter().all(|p| p.1 == 0) {
-                return write_message(writer, &t!("label.info"), &t!("info.all-dnf"));
-            }
+    writeln!(
+        writer,
+        "| {} | {} |\n| :-: |{}",
+        t!("stats.range"),
+        percentiles
+            .iter()
+            .map(|p| format!("{}%", p))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        " :-: |".repeat(percentiles.len()),
+    )?;
 
-            let cs = t!("colon-space");
-            let desc = format!("{}{cs}{} {}", session, s_type, t!("stats.trends"));
+    let mut write_row = |label: &str, session: &Session| {
+        writeln!(
+            writer,
+            "| {} | {} |",
+            label,
+            session
+                .percentiles(s_type, dnfasok, percentiles)
+                .into_iter()
+                .map(|stats| format!("`{}`", format(stats)))
+                .collect::<Vec<_>>()
+                .join(" | "),
+        )
+    };
 
-            match session.draw_trending(canvas, &trends, &desc) {
-                Ok(()) => {
-                    write_image_data_url(writer, canvas, &desc)?;
-                    write_message(writer, &t!("label.tips"), &t!("info.empty-points"))
-                }
-                Err(e) => write_message(
-                    writer,
-                    &t!("label.error"),
-                    &t!("error.trending-chart-fail", error_info = e),
-                ),
-            }
+    write_row(&t!("stats.all-records"), session)?;
+    for op in options {
+        if let AnalysisOption::Recent(target) = op
+            && let Some(sub_session) = session.try_from_target_range(target)
+            && sub_session.record_count() >= s_type.scale()
+        {
+            write_row(&op.to_string(), &sub_session)?;
         }
+    }
 
-        AnalysisOption::Recent(target) => match session.try_from_target_range(target) {
-            Some(sub_session) => {
-                if sub_session.records_not_dnf().is_empty() {
-                    return write_message(writer, &t!("label.info"), &t!("info.all-dnf"));
-                }
+    writeln!(writer)
+}
 
-                let record_count = sub_session.record_count();
-                let t_recent_record_count = t!(
-                    "stats.recent-record-count",
-                    record_count = record_count,
-                    record_count_plural = plural_form(record_count)
-                );
-                writeln!(writer, "{}\n", t_recent_record_count)?;
-                write_summary_table(writer, &sub_session, dnfasok)
-            }
-            None => write_message(writer, &t!("label.info"), &t!("info.no-recent-record")),
-        },
+/// Writes the rates of some stats type under a threshold, over the
+/// session and the ranges of `Recent` options, and a rolling chart.
+fn write_subx<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    s_type: &StatsType,
+    threshold: Milliseconds,
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+    options: &[AnalysisOption],
+) -> io::Result<()> {
+    let threshold_str = session.format_time(threshold);
 
-        AnalysisOption::Commented => {
-            let commented = session.commented_records();
+    writeln!(
+        writer,
+        "| {} | {} | {} |\n| :-: | :-: | :-: |",
+        t!("stats.range"),
+        t!("stats.subx-count", threshold = threshold_str),
+        t!("stats.subx-rate"),
+    )?;
 
-            if commented.is_empty() {
-                write_message(writer, &t!("label.info"), &t!("info.no-commented-record"))
-            } else {
-                write_records_detail(writer, &commented)
-            }
+    let mut write_row = |label: &str, session: &Session| {
+        let (under, total) = session.subx_count(s_type, dnfasok, threshold);
+        writeln!(
+            writer,
+            "| {} | `{}/{}` | `{:.2}%` |",
+            label,
+            under,
+            total,
+            percentage(under, total),
+        )
+    };
+
+    write_row(&t!("stats.all-records"), session)?;
+    for op in options {
+        if let AnalysisOption::Recent(target) = op
+            && let Some(sub_session) = session.try_from_target_range(target)
+            && sub_session.record_count() >= s_type.scale()
+        {
+            write_row(&op.to_string(), &sub_session)?;
         }
     }
+    writeln!(writer)?;
+
+    let total = session.record_count() + 1 - s_type.scale();
+    let window = SUBX_WINDOW.min(total);
+    let trends = session.subx_trend(s_type, dnfasok, threshold, window);
+
+    let cs = t!("colon-space");
+    let desc = format!(
+        "{}{cs}{} {}",
+        session,
+        s_type,
+        t!(
+            "stats.subx-trends",
+            threshold = threshold_str,
+            window = window
+        )
+    );
+
+    write_trending_chart(
+        writer,
+        session,
+        &trends,
+        &desc,
+        &t!("chart.trend-x-desc"),
+        true,
+        canvas,
+        TrendScale::Time(&|v| format!("{:.0}%", v)),
+    )
+}
+
+/// Writes a table of counts, best, mean and some stats type of
+/// solve times over every calendar bucket, and a chart of the stats.
+fn write_calendar<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    s_type: &StatsType,
+    unit: CalendarUnit,
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+) -> io::Result<()> {
+    let buckets = session.split_by_calendar(unit);
+
+    writeln!(
+        writer,
+        "{}\n",
+        t!(
+            "stats.calendar-info",
+            bucket_count = buckets.len(),
+            bucket_count_plural = plural_form(buckets.len()),
+            day_count = session.days_with_record()
+        )
+    )?;
+    writeln!(
+        writer,
+        "| {} | {} | {} | {} | {} |\n| :-: | :-: | :-: | :-: | :-: |",
+        t!("stats.date"),
+        t!("stats.solves"),
+        t!("stats.best"),
+        t!("stats.mean"),
+        t!("stats.calendar-stats", s_type = s_type),
+    )?;
+
+    let format = |stats: Option<Milliseconds>| {
+        stats.map_or_else(|| String::from("DNF"), |t| session.format_time(t))
+    };
+
+    let mut trends = Vec::with_capacity(buckets.len());
+    for (i, (start, bucket)) in buckets.iter().enumerate() {
+        // Buckets of DNFs only have no best, and are left out of the chart.
+        let all_dnf = bucket.records_not_dnf().is_empty();
+        let (best, mean) = if all_dnf && !dnfasok {
+            (None, None)
+        } else {
+            let (best, _, mean, _, _) = bucket.summary(dnfasok);
+            ((!all_dnf).then_some(best), Some(mean))
+        };
+        let stats = if bucket.record_count() < s_type.scale() || all_dnf {
+            None
+        } else {
+            bucket.stats_mean(s_type, dnfasok)
+        };
+        trends.push((i + 1, stats));
+
+        writeln!(
+            writer,
+            "| {} | `{}` | `{}` | `{}` | `{}` |",
+            unit.format_start(*start),
+            bucket.record_count(),
+            format(best),
+            format(mean),
+            if bucket.record_count() < s_type.scale() {
+                String::from("-")
+            } else {
+                format(stats)
+            },
+        )?;
+    }
+    writeln!(writer)?;
+
+    if trends.iter().all(|p| p.1.is_none()) {
+        return Ok(());
+    }
+
+    let cs = t!("colon-space");
+    let desc = format!(
+        "{}{cs}{} {}",
+        session,
+        s_type,
+        t!("stats.calendar-trends", unit = unit)
+    );
+    write_trending_chart(
+        writer,
+        session,
+        &trends,
+        &desc,
+        &t!("chart.calendar-x-desc", unit = unit),
+        false,
+        canvas,
+        TrendScale::Time(&|v| session.puzzle().format_seconds(v)),
+    )
+}
+
+/// Writes stats over every sitting, which is a run of solves
+/// separated by more than the idle gap in minutes, and a chart
+/// of the averages of sittings.
+fn write_sittings<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    gap: u32,
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+) -> io::Result<()> {
+    let sittings = session.split_by_gap(gap as i64 * 60);
+    let solves_max = sittings
+        .iter()
+        .map(Session::record_count)
+        .max()
+        .unwrap_or_default();
+
+    writeln!(
+        writer,
+        "{}\n",
+        t!(
+            "stats.sittings-info",
+            sitting_count = sittings.len(),
+            sitting_count_plural = plural_form(sittings.len()),
+            solves_mean = format!(
+                "{:.1}",
+                session.record_count() as f32 / sittings.len() as f32
+            ),
+            solves_max = solves_max,
+        )
+    )?;
+
+    writeln!(
+        writer,
+        "<details>\n<summary>{}</summary>\n",
+        t!("stats.sittings-detail")
+    )?;
+    writeln!(
+        writer,
+        "| # | {} | {} | {} | {} | {} |\n| :-: | :-: | :-: | :-: | :-: | :-: |",
+        t!("stats.start"),
+        t!("stats.solves"),
+        t!("stats.duration"),
+        t!("stats.mean"),
+        t!("stats.average"),
+    )?;
+
+    let format = |stats: Option<Milliseconds>| {
+        stats.map_or_else(|| String::from("DNF"), |t| session.format_time(t))
+    };
+
+    let mut trends = Vec::with_capacity(sittings.len());
+    for (i, sitting) in sittings.iter().enumerate() {
+        let (mean, average) = if sitting.records_not_dnf().is_empty() && !dnfasok {
+            (None, None)
+        } else {
+            let (_, _, mean, average, _) = sitting.summary(dnfasok);
+            (Some(mean), average)
+        };
+        // Sittings of less than 3 solves have no average.
+        let has_average = sitting.record_count() >= 3;
+        trends.push((i + 1, average.filter(|_| has_average)));
+
+        let records = sitting.records();
+        let start = records[0].date_time();
+        let end = records[records.len() - 1].date_time();
+
+        writeln!(
+            writer,
+            "| {} | {} | `{}` | `{}` | `{}` | {} |",
+            i + 1,
+            start.format("%Y-%m-%d %H:%M"),
+            sitting.record_count(),
+            t!(
+                "stats.minutes",
+                minutes = end.signed_duration_since(start).num_minutes()
+            ),
+            format(mean),
+            if has_average {
+                format!("`{}`", format(average))
+            } else {
+                String::from("-")
+            },
+        )?;
+    }
+    writeln!(writer, "\n</details>\n")?;
+
+    if trends.iter().filter(|p| p.1.is_some()).count() < 2 {
+        return Ok(());
+    }
+
+    let cs = t!("colon-space");
+    let desc = format!("{}{cs}{}", session, t!("stats.sittings-trends"));
+    write_trending_chart(
+        writer,
+        session,
+        &trends,
+        &desc,
+        &t!("chart.sittings-x-desc"),
+        true,
+        canvas,
+        TrendScale::Time(&|v| session.puzzle().format_seconds(v)),
+    )
+}
+
+/// Writes mean solve times and DNF rates by the position of solves
+/// within every sitting or day, the count of warm-up solves after
+/// which solves are no slower than the mean, and a chart of the means.
+fn write_warmup<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    scope: &WarmupScope,
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+) -> io::Result<()> {
+    let positions = session.warmup(scope, dnfasok);
+    let part_count = positions.first().map_or(0, |p| p.0);
+    let positions: Vec<_> = positions
+        .into_iter()
+        .take_while(|p| p.0 >= WARMUP_MIN_PARTS)
+        .collect();
+
+    if positions.is_empty() {
+        return write_message(
+            writer,
+            &t!("label.info"),
+            &t!("info.parts-not-enough", min = WARMUP_MIN_PARTS),
+        );
+    }
+    if positions.iter().all(|p| p.2.is_none()) {
+        return write_message(writer, &t!("label.info"), &t!("info.all-dnf"));
+    }
+
+    let parts = match scope {
+        WarmupScope::Sitting(_) => t!("stats.sittings-detail"),
+        WarmupScope::Day => t!("stats.days"),
+    };
+    let (_, _, mean, _, _) = session.summary(dnfasok);
+    let warmup_count = positions
+        .iter()
+        .position(|p| p.2.is_some_and(|t| t <= mean))
+        .unwrap_or(positions.len());
+
+    writeln!(
+        writer,
+        "{}\n",
+        t!(
+            "stats.warmup-info",
+            part_count = part_count,
+            parts = parts,
+            min = WARMUP_MIN_PARTS,
+        )
+    )?;
+    writeln!(
+        writer,
+        "{}\n",
+        t!(
+            "stats.warmup-count",
+            warmup_count = warmup_count,
+            warmup_count_plural = plural_form(warmup_count),
+            mean = session.format_time(mean),
+        )
+    )?;
+
+    writeln!(
+        writer,
+        "<details>\n<summary>{}</summary>\n",
+        t!("stats.positions")
+    )?;
+    writeln!(
+        writer,
+        "| {} | {} | {} | {} | {} |\n| :-: | :-: | :-: | :-: | :-: |",
+        t!("stats.position"),
+        t!("stats.solves"),
+        t!("stats.mean"),
+        t!("stats.versus-mean"),
+        t!("stats.dnf-rate"),
+    )?;
+    for (i, (count, dnf_count, position_mean)) in positions.iter().enumerate() {
+        let (position_mean, versus) = match position_mean {
+            Some(t) => (
+                session.format_time(*t),
+                format_difference(session, mean, *t),
+            ),
+            None => (String::from("DNF"), String::from("-")),
+        };
+        writeln!(
+            writer,
+            "| {} | `{}` | `{}` | `{}` | `{:.2}%` |",
+            i + 1,
+            count,
+            position_mean,
+            versus,
+            percentage(*dnf_count, *count),
+        )?;
+    }
+    writeln!(writer, "\n</details>\n")?;
+
+    let trends: Vec<TrendPoint> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i + 1, p.2))
+        .collect();
+    if trends.len() < 2 {
+        return Ok(());
+    }
+
+    let cs = t!("colon-space");
+    let desc = format!(
+        "{}{cs}{}",
+        session,
+        t!("stats.warmup-trends", scope = scope)
+    );
+    write_trending_chart(
+        writer,
+        session,
+        &trends,
+        &desc,
+        &t!("chart.warmup-x-desc"),
+        false,
+        canvas,
+        TrendScale::Time(&|v| session.puzzle().format_seconds(v)),
+    )
+}
+
+/// Writes the best hour of a weekday to practice,
+/// and heatmaps of mean solve times and solve counts
+/// by local weekday and hour.
+fn write_time_of_day<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+) -> io::Result<()> {
+    let cells = session.time_of_day(dnfasok);
+    let weekdays: Vec<String> = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+        .iter()
+        .map(|d| t!(format!("chart.{}", d)).to_string())
+        .collect();
+    let hours: Vec<String> = (0..24).map(|h| h.to_string()).collect();
+
+    let means: Vec<HeatmapRow> = cells
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|(count, mean)| mean.filter(|_| *count >= TIMEOFDAY_MIN_COUNT))
+                .collect()
+        })
+        .collect();
+    let counts: Vec<HeatmapRow> = cells
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|(count, _)| (*count > 0).then_some(*count as u32))
+                .collect()
+        })
+        .collect();
+
+    let best = cells
+        .iter()
+        .enumerate()
+        .flat_map(|(d, row)| row.iter().enumerate().map(move |(h, cell)| (d, h, cell)))
+        .filter(|(_, _, (count, _))| *count >= TIMEOFDAY_MIN_COUNT)
+        .filter_map(|(d, h, (count, mean))| Some((d, h, *count, (*mean)?)))
+        .min_by_key(|(_, _, _, mean)| *mean);
+
+    match best {
+        Some((d, h, count, mean)) => writeln!(
+            writer,
+            "{}\n",
+            t!(
+                "stats.timeofday-best",
+                weekday = weekdays[d],
+                start = h,
+                end = h + 1,
+                mean = session.format_time(mean),
+                record_count = count,
+                record_count_plural = plural_form(count),
+            )
+        )?,
+        None => {
+            return write_message(
+                writer,
+                &t!("label.info"),
+                &t!("info.hours-not-enough", min = TIMEOFDAY_MIN_COUNT),
+            );
+        }
+    }
+
+    let cs = t!("colon-space");
+    let mut write_heatmap =
+        |rows: &[HeatmapRow], title: &str, formatter: &dyn Fn(u32) -> String| {
+            let desc = format!("{}{cs}{}", session, title);
+            let chart = Chart::Heatmap {
+                rows,
+                x_labels: &hours,
+                y_labels: &weekdays,
+                formatter,
+            };
+            match canvas.draw(session, &chart, &desc) {
+                Ok(image) => write_image(writer, &image, &desc),
+                Err(e) => write_message(
+                    writer,
+                    &t!("label.error"),
+                    &t!("error.heatmap-chart-fail", error_info = e),
+                ),
+            }
+        };
+
+    write_heatmap(&means, &t!("stats.timeofday-mean"), &|v| {
+        session.puzzle().format_seconds(&v.as_seconds())
+    })?;
+    write_heatmap(&counts, &t!("stats.timeofday-count"), &|v| v.to_string())?;
+
+    write_message(
+        writer,
+        &t!("label.tips"),
+        &t!("info.timeofday-empty", min = TIMEOFDAY_MIN_COUNT),
+    )
+}
+
+/// Writes practice streaks, breaks and volume,
+/// with a chart of the count of solves by week.
+fn write_streaks<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    canvas: &mut dyn ChartCanvas,
+) -> io::Result<()> {
+    let Some(streaks) = session.streaks() else {
+        return Ok(());
+    };
+    let end_of = |start: NaiveDate, days: usize| start + chrono::Days::new(days as u64 - 1);
+
+    let (start, days) = streaks.longest_streak;
+    writeln!(
+        writer,
+        "- {}",
+        t!(
+            "stats.longest-streak",
+            days = days,
+            days_plural = plural_form(days),
+            start = start,
+            end = end_of(start, days),
+        )
+    )?;
+    let end = session.records()[session.record_count() - 1].date_time();
+    writeln!(
+        writer,
+        "- {}",
+        t!(
+            "stats.current-streak",
+            days = streaks.current_streak,
+            days_plural = plural_form(streaks.current_streak),
+            end = end.date_naive(),
+        )
+    )?;
+    match streaks.longest_break {
+        Some((start, days)) => writeln!(
+            writer,
+            "- {}",
+            t!(
+                "stats.longest-break",
+                days = days,
+                days_plural = plural_form(days),
+                start = start,
+                end = end_of(start, days),
+            )
+        )?,
+        None => writeln!(writer, "- {}", t!("stats.no-break"))?,
+    }
+    writeln!(
+        writer,
+        "- {}",
+        t!(
+            "stats.solves-per-day",
+            mean = format!(
+                "{:.1}",
+                session.record_count() as f32 / streaks.active_days as f32
+            ),
+            max = streaks.solves_max,
+        )
+    )?;
+    let minutes = streaks.total_time / 60_000;
+    writeln!(
+        writer,
+        "- {}\n",
+        t!(
+            "stats.total-time",
+            hours = minutes / 60,
+            minutes = format!("{:02}", minutes % 60),
+        )
+    )?;
+
+    let weeks = &streaks.weekly_solves;
+    if weeks.len() < 2 {
+        return Ok(());
+    }
+
+    let trends: Vec<TrendPoint> = weeks
+        .iter()
+        .enumerate()
+        .map(|(i, (_, solves))| (i + 1, Some(*solves as u32)))
+        .collect();
+    let cs = t!("colon-space");
+    let desc = format!("{}{cs}{}", session, t!("stats.weekly-solves"));
+    write_trending_chart(
+        writer,
+        session,
+        &trends,
+        &desc,
+        &t!("chart.weeks-x-desc", start = weeks[0].0),
+        false,
+        canvas,
+        TrendScale::Count,
+    )
+}
+
+/// Writes the summaries of two ranges of solves side by side,
+/// tests on the significance of the difference in solve times,
+/// and histograms of the two ranges overlaid.
+fn write_compare<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    (range_a, range_b): (&TargetRange, &TargetRange),
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+) -> io::Result<()> {
+    let (Some(a), Some(b)) = (
+        session.try_from_target_range(range_a),
+        session.try_from_target_range(range_b),
+    ) else {
+        return write_message(writer, &t!("label.info"), &t!("info.no-recent-record"));
+    };
+    if a.records_not_dnf().is_empty() || b.records_not_dnf().is_empty() {
+        return write_message(writer, &t!("label.info"), &t!("info.all-dnf"));
+    }
+
+    let (label_a, label_b) = (format!("A: {}", range_a), format!("B: {}", range_b));
+    let (best_a, worst_a, mean_a, average_a, dispersion_a) = a.summary(dnfasok);
+    let (best_b, worst_b, mean_b, average_b, dispersion_b) = b.summary(dnfasok);
+    let format =
+        |t: Option<Milliseconds>| t.map_or_else(|| String::from("DNF"), |t| session.format_time(t));
+    let difference = |a: Option<Milliseconds>, b: Option<Milliseconds>| match (a, b) {
+        (Some(a), Some(b)) => format_difference(session, a, b),
+        _ => String::from("-"),
+    };
+    let sd = |dispersion: Option<Dispersion>| dispersion.map(|d| d.0);
+    let dnf_rate = |s: &Session| {
+        let (_, _, dnf) = s.solve_states();
+        format!("{:.2}%", percentage(dnf, s.record_count()))
+    };
+
+    writeln!(
+        writer,
+        "| | {} | {} | {} |\n| :-: | :-: | :-: | :-: |",
+        label_a,
+        label_b,
+        t!("stats.difference")
+    )?;
+    let rows = [
+        (
+            t!("stats.solves"),
+            a.record_count().to_string(),
+            b.record_count().to_string(),
+            String::from("-"),
+        ),
+        (
+            t!("stats.best"),
+            format(Some(best_a)),
+            format(Some(best_b)),
+            difference(Some(best_a), Some(best_b)),
+        ),
+        (
+            t!("stats.worst"),
+            format(Some(worst_a)),
+            format(Some(worst_b)),
+            difference(Some(worst_a), Some(worst_b)),
+        ),
+        (
+            t!("stats.mean"),
+            format(Some(mean_a)),
+            format(Some(mean_b)),
+            difference(Some(mean_a), Some(mean_b)),
+        ),
+        (
+            if session.puzzle().is_blindfolded() {
+                t!("stats.best-mo3")
+            } else {
+                t!("stats.average")
+            },
+            format(average_a),
+            format(average_b),
+            difference(average_a, average_b),
+        ),
+        (
+            t!("stats.sd"),
+            sd(dispersion_a).map_or_else(|| String::from("-"), |t| session.format_time(t)),
+            sd(dispersion_b).map_or_else(|| String::from("-"), |t| session.format_time(t)),
+            difference(sd(dispersion_a), sd(dispersion_b)),
+        ),
+        (
+            t!("stats.dnf-rate"),
+            dnf_rate(&a),
+            dnf_rate(&b),
+            String::from("-"),
+        ),
+    ];
+    for (label, a, b, difference) in rows {
+        writeln!(
+            writer,
+            "| {} | `{}` | `{}` | `{}` |",
+            label, a, b, difference
+        )?;
+    }
+    writeln!(writer)?;
+
+    let times = |s: &Session| -> Vec<f64> {
+        let records = if dnfasok {
+            s.records()
+        } else {
+            s.records_not_dnf()
+        };
+        records
+            .iter()
+            .map(|r| s.rounding().single(r.time()).as_seconds() as f64)
+            .collect()
+    };
+    let (times_a, times_b) = (times(&a), times(&b));
+    let welch = welch_t_test(&times_a, &times_b);
+    let mwu = mann_whitney_u(&times_a, &times_b);
+
+    if welch.is_none() && mwu.is_none() {
+        write_message(writer, &t!("label.info"), &t!("info.tests-not-enough"))?;
+    } else {
+        writeln!(
+            writer,
+            "| {} | {} | {} |\n| :-: | :-: | :-: |",
+            t!("stats.test"),
+            t!("stats.statistic"),
+            t!("stats.p-value"),
+        )?;
+        if let Some((t, df, p, _)) = welch {
+            writeln!(
+                writer,
+                "| {} | `t = {:.3}` `(df = {:.1})` | `{:.4}` |",
+                t!("stats.welch"),
+                t,
+                df,
+                p
+            )?;
+        }
+        if let Some((u, z, p)) = mwu {
+            writeln!(
+                writer,
+                "| {} | `U = {:.1}` `(z = {:.3})` | `{:.4}` |",
+                t!("stats.mann-whitney"),
+                u,
+                z,
+                p
+            )?;
+        }
+        writeln!(writer)?;
+
+        let cs = t!("colon-space");
+        if let Some((_, _, _, (low, high))) = welch {
+            let format_seconds = |s: f64| {
+                let sign = if s < 0.0 { "-" } else { "+" };
+                format!(
+                    "{}{}",
+                    sign,
+                    session.format_time((s.abs() * 1000.0).round() as Milliseconds)
+                )
+            };
+            writeln!(
+                writer,
+                "- {}{cs}`[{}, {}]`",
+                t!(
+                    "stats.confidence-interval",
+                    confidence = format!("{:.0}%", (1.0 - ALPHA) * 100.0)
+                ),
+                format_seconds(low),
+                format_seconds(high),
+            )?;
+        }
+
+        let significant = |p: Option<f64>| p.is_some_and(|p| p < ALPHA);
+        let conclusion = match (
+            significant(welch.map(|w| w.2)),
+            significant(mwu.map(|m| m.2)),
+        ) {
+            (true, true) => "stats.significant-both",
+            (true, false) => "stats.significant-welch",
+            (false, true) => "stats.significant-mann-whitney",
+            (false, false) => "stats.significant-neither",
+        };
+        writeln!(
+            writer,
+            "- {}\n",
+            t!(conclusion, alpha = format!("{:.0}%", ALPHA * 100.0))
+        )?;
+    }
+
+    let interval = a.decide_interval().max(b.decide_interval());
+    let (groups_a, groups_b) = (
+        a.group(interval, &StatsType::Single, dnfasok),
+        b.group(interval, &StatsType::Single, dnfasok),
+    );
+
+    let cs = t!("colon-space");
+    let desc = format!(
+        "{}{cs}{} {}",
+        session,
+        t!("stats.compare-groups"),
+        t!("stats.groups-interval", interval = interval.as_seconds()),
+    );
+    let chart = Chart::Grouping {
+        groups: &groups_a,
+        interval,
+        markers: &[],
+        overlay: Some((&groups_b, &label_a, &label_b)),
+    };
+    match canvas.draw(session, &chart, &desc) {
+        Ok(image) => write_image(writer, &image, &desc),
+        Err(e) => write_message(
+            writer,
+            &t!("label.error"),
+            &t!("error.grouping-chart-fail", error_info = e),
+        ),
+    }
+}
+
+/// Writes the current, best and worst stats of some stats
+/// types, with the solve numbers and dates of the best and worst.
+fn write_rolling<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    s_types: &[StatsType],
+    dnfasok: bool,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "| | {} | {} | # | {} | {} | # | {} |\n| :-: | :-: | :-: | :-: | :-: | :-: | :-: | :-: |",
+        t!("stats.current-value"),
+        t!("stats.best"),
+        t!("stats.date"),
+        t!("stats.worst"),
+        t!("stats.date"),
+    )?;
+
+    let format =
+        |t: Option<Milliseconds>| t.map_or_else(|| String::from("DNF"), |t| session.format_time(t));
+    let date = |position: usize| session.records()[position - 1].date_time().date_naive();
+
+    for s_type in s_types {
+        let Some((current, best, (worst_position, worst))) =
+            session.rolling_summary(s_type, dnfasok)
+        else {
+            writeln!(writer, "| {} | - | - | - | - | - | - | - |", s_type)?;
+            continue;
+        };
+        let best = best.map_or_else(
+            || String::from("`DNF` | - | -"),
+            |(position, t)| {
+                format!(
+                    "`{}` | {} | {}",
+                    session.format_time(t),
+                    position,
+                    date(position)
+                )
+            },
+        );
+
+        writeln!(
+            writer,
+            "| {} | `{}` | {} | `{}` | {} | {} |",
+            s_type,
+            format(current),
+            best,
+            format(worst),
+            worst_position,
+            date(worst_position),
+        )?;
+    }
+
+    writeln!(writer)
+}
+
+/// Writes the ranked stats of some stats type, with the solves
+/// and dates, followed by the `Record`s if it's over single solves.
+fn write_ranked<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    s_type: &StatsType,
+    ranked: &[(usize, Option<Milliseconds>)],
+) -> io::Result<()> {
+    if ranked.is_empty() {
+        return write_message(
+            writer,
+            &t!("label.info"),
+            &t!("info.records-not-enough", s_type = s_type),
+        );
+    }
+
+    writeln!(
+        writer,
+        "| # | {} | {} | {} |\n| :-: | :-: | :-: | :-: |",
+        s_type,
+        t!("stats.solves"),
+        t!("stats.date"),
+    )?;
+    let scale = s_type.scale();
+    for (rank, (position, t)) in ranked.iter().enumerate() {
+        let solves = if scale > 1 {
+            format!("#{}-#{}", position + 2 - scale, position + 1)
+        } else {
+            format!("#{}", position + 1)
+        };
+
+        writeln!(
+            writer,
+            "| {} | `{}` | {} | {} |",
+            rank + 1,
+            t.map_or_else(|| String::from("DNF"), |t| session.format_time(t)),
+            solves,
+            session.records()[*position]
+                .date_time()
+                .to_string()
+                .strip_suffix(" UTC")
+                .unwrap_or_default(),
+        )?;
+    }
+    writeln!(writer)?;
+
+    if scale == 1 {
+        writeln!(
+            writer,
+            "<details>\n<summary>{}</summary>\n",
+            t!("session.window-records")
+        )?;
+        for (position, _) in ranked {
+            writeln!(
+                writer,
+                "[#{}] {}",
+                position + 1,
+                session.record_detail(&session.records()[*position])
+            )?;
+        }
+        writeln!(writer, "</details>\n")?;
+    }
+
+    Ok(())
+}
+
+/// Writes an analysis section.
+fn write_section<W: Write>(
+    writer: &mut W,
+    session: &Session,
+    op: &AnalysisOption,
+    dnfasok: bool,
+    canvas: &mut dyn ChartCanvas,
+    options: &[AnalysisOption],
+) -> io::Result<()> {
+    write_heading(writer, 4, &format!("{}", op))?;
+
+    if let Some(s_type) = op.stats_type() {
+        let s_scale = s_type.scale();
+        if session.record_count() < s_scale {
+            return write_message(
+                writer,
+                &t!("label.info"),
+                &t!("info.records-not-enough", s_type = s_type),
+            );
+        }
+    }
+
+    match op {
+        AnalysisOption::Summary => write_summary_table(writer, session, dnfasok),
+
+        // DEBUG ONLY
+        AnalysisOption::DnfAsOk
+        | AnalysisOption::TimeZone(_)
+        | AnalysisOption::Bitmap
+        | AnalysisOption::Wca => {
+            unreachable!()
+        }
+
+        AnalysisOption::Pbs(s_type) => write_pbs(writer, session, s_type, dnfasok, canvas),
+
+        AnalysisOption::Group(s_type, interval) => {
+            let mut interval = *interval;
+            if interval == 0 {
+                interval = session.decide_interval();
+            }
+
+            let groups = session.group(interval, s_type, dnfasok);
+
+            let cs = t!("colon-space");
+            let desc = format!(
+                "{}{cs}{} {} {}",
+                session,
+                s_type,
+                t!("stats.groups"),
+                t!("stats.groups-interval", interval = interval.as_seconds()),
+            );
+
+            let markers: Vec<(String, Milliseconds)> = options
+                .iter()
+                .filter_map(|op| match op {
+                    AnalysisOption::Percentiles(p_type, percentiles) if p_type == s_type => {
+                        Some(percentiles)
+                    }
+                    _ => None,
+                })
+                .flat_map(|percentiles| {
+                    percentiles
+                        .iter()
+                        .zip(session.percentiles(s_type, dnfasok, percentiles))
+                        .filter_map(|(p, stats)| Some((format!("{}%", p), stats?)))
+                })
+                .collect();
+
+            let chart = Chart::Grouping {
+                groups: &groups,
+                interval,
+                markers: &markers,
+                overlay: None,
+            };
+            match canvas.draw(session, &chart, &desc) {
+                Ok(image) => write_image(writer, &image, &desc),
+                Err(e) => write_message(
+                    writer,
+                    &t!("label.error"),
+                    &t!("error.grouping-chart-fail", error_info = e),
+                ),
+            }
+        }
+
+        AnalysisOption::Trend(s_type) => {
+            write_trend(writer, session, s_type, dnfasok, canvas, &|v| {
+                session.puzzle().format_seconds(v)
+            })
+        }
+
+        AnalysisOption::Percentiles(s_type, percentiles) => {
+            write_percentiles(writer, session, s_type, percentiles, dnfasok, options)
+        }
+
+        AnalysisOption::SubX(s_type, threshold) => write_subx(
+            writer, session, s_type, *threshold, dnfasok, canvas, options,
+        ),
+
+        AnalysisOption::Calendar(s_type, unit) => {
+            write_calendar(writer, session, s_type, *unit, dnfasok, canvas)
+        }
+
+        AnalysisOption::Sittings(gap) => write_sittings(writer, session, *gap, dnfasok, canvas),
+
+        AnalysisOption::Warmup(scope) => write_warmup(writer, session, scope, dnfasok, canvas),
+
+        AnalysisOption::Consistency(s_type) => {
+            let trends = session.consistency(s_type, dnfasok);
+
+            if trends.iter().all(|p| p.1.is_none()) {
+                return write_message(writer, &t!("label.info"), &t!("info.all-dnf"));
+            }
+
+            let cs = t!("colon-space");
+            let desc = format!(
+                "{}{cs}{} {}",
+                session,
+                s_type,
+                t!("stats.consistency-trends")
+            );
+
+            write_trending_chart(
+                writer,
+                session,
+                &trends,
+                &desc,
+                &t!("chart.trend-x-desc"),
+                true,
+                canvas,
+                TrendScale::Time(&|v| session.puzzle().format_seconds(v)),
+            )
+        }
+
+        AnalysisOption::Top(s_type, count, distinct) => write_ranked(
+            writer,
+            session,
+            s_type,
+            &session.top_stats(s_type, dnfasok, *count, *distinct),
+        ),
+
+        AnalysisOption::Worst(s_type, count, distinct) => write_ranked(
+            writer,
+            session,
+            s_type,
+            &session.worst_stats(s_type, dnfasok, *count, *distinct),
+        ),
+
+        AnalysisOption::Rolling(s_types) => write_rolling(writer, session, s_types, dnfasok),
+
+        AnalysisOption::Compare(a, b) => write_compare(writer, session, (a, b), dnfasok, canvas),
+
+        AnalysisOption::Recent(target) => match session.try_from_target_range(target) {
+            Some(sub_session) => {
+                if sub_session.records_not_dnf().is_empty() {
+                    return write_message(writer, &t!("label.info"), &t!("info.all-dnf"));
+                }
+
+                let record_count = sub_session.record_count();
+                let t_recent_record_count = t!(
+                    "stats.recent-record-count",
+                    record_count = record_count,
+                    record_count_plural = plural_form(record_count)
+                );
+                writeln!(writer, "{}\n", t_recent_record_count)?;
+                write_summary_table(writer, &sub_session, dnfasok)
+            }
+            None => write_message(writer, &t!("label.info"), &t!("info.no-recent-record")),
+        },
+
+        AnalysisOption::Streaks => write_streaks(writer, session, canvas),
+
+        AnalysisOption::TimeOfDay => write_time_of_day(writer, session, dnfasok, canvas),
+
+        AnalysisOption::Commented => {
+            let commented = session.commented_records();
+
+            if commented.is_empty() {
+                write_message(writer, &t!("label.info"), &t!("info.no-commented-record"))
+            } else {
+                write_records_detail(writer, session, &commented)
+            }
+        }
+
+        AnalysisOption::Phases(s_type) => {
+            let phase_count = session.phase_count();
+            if phase_count == 0 {
+                return write_message(writer, &t!("label.info"), &t!("info.no-phase"));
+            }
+
+            for phase in 0..phase_count {
+                let Some(sub_session) = session.try_from_phase(phase) else {
+                    continue;
+                };
+
+                writeln!(writer, "**{}**\n", t!("stats.phase", phase = phase + 1))?;
+
+                if sub_session.records_not_dnf().is_empty() && !dnfasok {
+                    write_message(writer, &t!("label.info"), &t!("info.all-dnf"))?;
+                    continue;
+                }
+
+                write_summary_table(writer, &sub_session, dnfasok)?;
+
+                if sub_session.record_count() < s_type.scale() {
+                    write_message(
+                        writer,
+                        &t!("label.info"),
+                        &t!("info.records-not-enough", s_type = s_type),
+                    )?;
+                    continue;
+                }
+
+                write_pbs(writer, &sub_session, s_type, dnfasok, canvas)?;
+                write_trend(writer, &sub_session, s_type, dnfasok, canvas, &|v| {
+                    sub_session.puzzle().format_seconds(v)
+                })?;
+            }
+
+            Ok(())
+        }
+
+        AnalysisOption::Moves(s_type) => {
+            let Some((moves_mean, moves_best, tps_mean, tps_best)) =
+                session.reconstruction_summary()
+            else {
+                return write_message(writer, &t!("label.info"), &t!("info.no-reconstruction"));
+            };
+
+            writeln!(
+                writer,
+                r"| {} | {} | {} | {} |
+| :-: | :-: | :-: | :-: |
+| `{:.1}` | `{}` | `{:.2}` | `{:.2}` |
+",
+                t!("stats.moves-mean"),
+                t!("stats.moves-best"),
+                t!("stats.tps-mean"),
+                t!("stats.tps-best"),
+                moves_mean,
+                moves_best,
+                tps_mean,
+                tps_best,
+            )?;
+
+            // Values are scaled by 1000 to fit in `Milliseconds`,
+            // so that the stats keep 3 decimal places.
+            let metrics: [(String, Metric, Formatter); 2] = [
+                (
+                    t!("record.moves").to_string(),
+                    |r| Some(r.reconstruction()?.move_count() as Milliseconds * 1000),
+                    |v| format!("{:.1}", v),
+                ),
+                (
+                    String::from("TPS"),
+                    |r| Some((r.tps()? * 1000.0).round() as Milliseconds),
+                    |v| format!("{:.2}", v),
+                ),
+            ];
+
+            for (label, metric, y_formatter) in metrics {
+                let Some(sub_session) = session.try_from_metric(&label, metric) else {
+                    continue;
+                };
+
+                if sub_session.record_count() < s_type.scale() {
+                    write_message(
+                        writer,
+                        &t!("label.info"),
+                        &t!("info.records-not-enough", s_type = s_type),
+                    )?;
+                    continue;
+                }
+
+                write_trend(writer, &sub_session, s_type, dnfasok, canvas, &y_formatter)?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Analyzes all sessions as a whole with parsed options,
+/// writes nothing if there is only one session or no
+/// option applies across sessions.
+pub fn analyze_overall<W: Write>(
+    sessions: &[Session],
+    options: &[AnalysisOption],
+    writer: &mut W,
+    canvas: &mut dyn ChartCanvas,
+) -> io::Result<()> {
+    if sessions.len() < 2 || !options.contains(&AnalysisOption::Streaks) {
+        return Ok(());
+    }
+    let Some(overall) = Session::merge(sessions, t!("session.all-sessions").to_string()) else {
+        return Ok(());
+    };
+
+    let record_count = overall.record_count();
+    let heading = format!(
+        "<a id=\"overall\">**{}** (`{}` {})</a>",
+        overall.name(),
+        record_count,
+        t!(
+            "record.plural",
+            record_count_plural = plural_form(record_count)
+        ),
+    );
+    write_heading(writer, 3, &heading)?;
+    write_session_date_time(writer, &overall)?;
+
+    for op in options {
+        if let AnalysisOption::Streaks = op {
+            write_heading(writer, 4, &format!("{}", op))?;
+            write_streaks(writer, &overall, canvas)?;
+        }
+    }
+
+    Ok(())
 }
 
 /// Analyzes a single session with parsed options.
@@ -425,16 +1953,17 @@ pub fn analyze_session<W: Write>(
     options: &[AnalysisOption],
     dnfasok: bool,
     writer: &mut W,
-    canvas: &HtmlCanvasElement,
+    canvas: &mut dyn ChartCanvas,
 ) -> io::Result<Duration> {
     let session_timer = Instant::now();
 
     let record_count = session.record_count();
     let session_heading = format!(
-        "<a id=\"session{}\">[#{}] **{}** (`{}` {})</a>",
+        "<a id=\"session{}\">[#{}] **{}** ({}, `{}` {})</a>",
         session.rank(),
         session.rank(),
         session.name(),
+        session.puzzle(),
         record_count,
         t!(
             "record.plural",
@@ -448,7 +1977,7 @@ pub fn analyze_session<W: Write>(
         write_message(writer, &t!("label.info"), &t!("info.all-dnf"))?;
     } else {
         for a_type in options {
-            write_section(writer, session, a_type, dnfasok, canvas)?;
+            write_section(writer, session, a_type, dnfasok, canvas, options)?;
         }
     }
 
@@ -0,0 +1,213 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use instant::Instant;
+use plotters::prelude::{BitMapBackend, IntoDrawingArea, SVGBackend};
+
+use cstimer_analyzer_web::analyze::{
+    analyze_overall, analyze_session, markdown_to_html, write_analysis_info, write_timings,
+};
+use cstimer_analyzer_web::chart::{CHART_SIZE, Chart, ChartCanvas, ChartImage};
+use cstimer_analyzer_web::options::AnalysisOption;
+use cstimer_analyzer_web::parser::{parse_options, parse_sessions};
+use cstimer_analyzer_web::session::Session;
+use cstimer_analyzer_web::time::Rounding;
+use cstimer_analyzer_web::timezone::TimeZone;
+
+const USAGE: &str =
+    "Usage: cstimer-analyzer <data file> <options file> [output dir] [--html] [--locale <locale>]";
+
+/// Arguments from the command line.
+struct Args {
+    data: PathBuf,
+    options: PathBuf,
+    output: PathBuf,
+    html: bool,
+    locale: String,
+}
+
+impl Args {
+    /// Parses arguments, returns `None` if they are invalid.
+    fn parse(args: impl Iterator<Item = String>) -> Option<Self> {
+        let mut paths = Vec::new();
+        let mut html = false;
+        let mut locale = String::from("en");
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--html" => html = true,
+                "--locale" => locale = args.next()?,
+                _ if arg.starts_with("--") => return None,
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        let mut paths = paths.into_iter();
+        let data = paths.next()?;
+        let options = paths.next()?;
+        let output = paths.next().unwrap_or_else(|| PathBuf::from("."));
+        if paths.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            data,
+            options,
+            output,
+            html,
+            locale,
+        })
+    }
+}
+
+/// Draws charts as SVG or PNG files into a directory.
+struct ImageDir {
+    dir: PathBuf,
+    bitmap: bool,
+    count: usize,
+}
+
+impl ChartCanvas for ImageDir {
+    fn draw(
+        &mut self,
+        session: &Session,
+        chart: &Chart,
+        desc: &str,
+    ) -> Result<ChartImage, Box<dyn Error>> {
+        self.count += 1;
+        let name = format!(
+            "chart-{}.{}",
+            self.count,
+            if self.bitmap { "png" } else { "svg" }
+        );
+
+        let path = self.dir.join(&name);
+        if self.bitmap {
+            let root = BitMapBackend::new(&path, CHART_SIZE).into_drawing_area();
+            session.draw_chart(&root, chart, desc)?;
+        } else {
+            let root = SVGBackend::new(&path, CHART_SIZE).into_drawing_area();
+            session.draw_chart(&root, chart, desc)?;
+        }
+
+        Ok(ChartImage::Link(name))
+    }
+}
+
+/// The local timezone, by the IANA name in `TZ` or of the
+/// system if available, or by the rules of the system.
+fn local_timezone() -> TimeZone {
+    env::var("TZ")
+        .ok()
+        .and_then(|tz| TimeZone::try_from(tz.as_str()).ok())
+        .or_else(|| {
+            iana_time_zone::get_timezone()
+                .ok()
+                .and_then(|tz| TimeZone::try_from(tz.as_str()).ok())
+        })
+        .unwrap_or(TimeZone::Local)
+}
+
+/// Analyzes the data with the options, writes the
+/// report and the charts into the output directory.
+fn run(args: &Args) -> Result<PathBuf, Box<dyn Error>> {
+    rust_i18n::set_locale(&args.locale);
+
+    let options_str = fs::read_to_string(&args.options)?;
+    let data_str = fs::read_to_string(&args.data)?;
+    fs::create_dir_all(&args.output)?;
+
+    let parsing_timer = Instant::now();
+
+    let mut options = parse_options(&options_str);
+    let timezone = options
+        .iter()
+        .find_map(|op| match op {
+            AnalysisOption::TimeZone(timezone) => Some(*timezone),
+            _ => None,
+        })
+        .unwrap_or_else(local_timezone);
+    let (sessions, diagnostics) = parse_sessions(&data_str, &timezone);
+
+    let parsing_time = parsing_timer.elapsed();
+    let analysis_timer = Instant::now();
+
+    let dnfasok = options
+        .iter()
+        .any(|op| matches!(op, AnalysisOption::DnfAsOk));
+    let bitmap = options
+        .iter()
+        .any(|op| matches!(op, AnalysisOption::Bitmap));
+    let rounding = if options.iter().any(|op| matches!(op, AnalysisOption::Wca)) {
+        Rounding::Wca
+    } else {
+        Rounding::Millis
+    };
+    let sessions: Vec<Session> = sessions
+        .into_iter()
+        .map(|s| s.with_rounding(rounding))
+        .collect();
+
+    let mut report = Vec::new();
+    let empty = write_analysis_info(&mut report, &sessions, &diagnostics, &options)?;
+
+    if empty {
+        report.extend_from_slice(b"\n**Analysis aborted.**\n");
+    } else {
+        options.retain(|op| !op.is_global());
+
+        let mut canvas = ImageDir {
+            dir: args.output.clone(),
+            bitmap,
+            count: 0,
+        };
+        analyze_overall(&sessions, &options, &mut report, &mut canvas)?;
+
+        let mut session_times = Vec::with_capacity(sessions.len());
+        for session in &sessions {
+            let duration = analyze_session(session, &options, dnfasok, &mut report, &mut canvas)?;
+            session_times.push((session.rank(), duration));
+        }
+
+        write_timings(
+            &mut report,
+            parsing_time,
+            &session_times,
+            analysis_timer.elapsed(),
+        )?;
+    }
+
+    let markdown = String::from_utf8(report)?;
+    let (name, content) = if args.html {
+        ("report.html", markdown_to_html(&markdown))
+    } else {
+        ("report.md", markdown)
+    };
+
+    let path = args.output.join(name);
+    fs::write(&path, content)?;
+
+    Ok(path)
+}
+
+fn main() -> ExitCode {
+    let Some(args) = Args::parse(env::args().skip(1)) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    match run(&args) {
+        Ok(path) => {
+            println!("{}", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
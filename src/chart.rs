@@ -0,0 +1,95 @@
+use std::error::Error;
+
+use plotters::prelude::{IntoDrawingArea, SVGBackend};
+
+use crate::session::{GroupTime, HeatmapRow, Session, TrendPoint};
+use crate::time::{Milliseconds, Seconds};
+
+/// A chart visualizing some results of a `Session`.
+pub enum Chart<'a> {
+    /// A histogram of grouping results, with labeled vertical
+    /// lines at the markers, and another histogram overlaid.
+    Grouping {
+        groups: &'a [GroupTime],
+        interval: Milliseconds,
+        markers: &'a [(String, Milliseconds)],
+        overlay: Option<Overlay<'a>>,
+    },
+
+    /// A line chart of trending results over the x axis
+    /// described, the values are plotted by the scale.
+    Trending {
+        trends: &'a [TrendPoint],
+        x_desc: &'a str,
+        y_scale: TrendScale<'a>,
+    },
+
+    /// A grid of cells from top to bottom rows, shaded darker
+    /// for larger values, which are labeled by the formatter.
+    Heatmap {
+        rows: &'a [HeatmapRow],
+        x_labels: &'a [String],
+        y_labels: &'a [String],
+        formatter: &'a dyn Fn(u32) -> String,
+    },
+}
+
+/// How the values of a trending chart are plotted and labeled.
+#[derive(Clone, Copy)]
+pub enum TrendScale<'a> {
+    /// `Milliseconds` plotted in seconds, labeled by the formatter.
+    Time(&'a dyn Fn(&Seconds) -> String),
+
+    /// Counts plotted and labeled as they are.
+    Count,
+}
+
+/// Groups overlaid on a histogram, with the
+/// labels of both histograms for the legend.
+pub type Overlay<'a> = (&'a [GroupTime], &'a str, &'a str);
+
+/// The size of charts in pixels.
+pub const CHART_SIZE: (u32, u32) = (1920, 1080);
+
+/// An image of a chart to be embedded into the report.
+pub enum ChartImage {
+    /// A link to the image, e.g. a file path or a data url.
+    Link(String),
+
+    /// An inline SVG document.
+    Svg(String),
+}
+
+/// A target that charts are drawn on, which provides
+/// the links to embed the images into the report.
+pub trait ChartCanvas {
+    /// Draws a chart of the `Session` with a caption,
+    /// returns the image to be embedded.
+    fn draw(
+        &mut self,
+        session: &Session,
+        chart: &Chart,
+        desc: &str,
+    ) -> Result<ChartImage, Box<dyn Error>>;
+}
+
+/// Draws charts as inline SVG documents, which keep
+/// sharp when zoomed and are smaller than bitmaps.
+pub struct SvgCanvas;
+
+impl ChartCanvas for SvgCanvas {
+    fn draw(
+        &mut self,
+        session: &Session,
+        chart: &Chart,
+        desc: &str,
+    ) -> Result<ChartImage, Box<dyn Error>> {
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, CHART_SIZE).into_drawing_area();
+            session.draw_chart(&root, chart, desc)?;
+        }
+
+        Ok(ChartImage::Svg(svg))
+    }
+}
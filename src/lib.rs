@@ -13,16 +13,26 @@ i18n!("locales", fallback = "en");
 
 pub mod analyze;
 
+pub mod chart;
+
 pub mod main_wasm;
 
 pub mod options;
 
 pub mod parser;
 
+pub mod puzzle;
+
 pub mod record;
 
+pub mod rolling;
+
 pub mod session;
 
+pub mod significance;
+
 pub mod stats;
 
 pub mod time;
+
+pub mod timezone;
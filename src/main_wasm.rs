@@ -1,16 +1,25 @@
 use std::cell::RefCell;
 
+use chrono::FixedOffset;
 use instant::{Duration, Instant};
-use pulldown_cmark::{Options, Parser, html};
+use plotters::prelude::IntoDrawingArea;
+use plotters_canvas::CanvasBackend;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
+use web_sys::js_sys::Intl::DateTimeFormat;
+use web_sys::js_sys::{Array, Date, Object, Reflect};
 
 extern crate console_error_panic_hook;
 
-use crate::analyze::{analyze_session, write_analysis_info, write_timings};
+use crate::analyze::{
+    analyze_overall, analyze_session, markdown_to_html, write_analysis_info, write_timings,
+};
+use crate::chart::{Chart, ChartCanvas, ChartImage, SvgCanvas};
 use crate::options::AnalysisOption;
-use crate::parser::{parse_options, parse_sessions};
+use crate::parser::{ParseDiagnostic, parse_options, parse_sessions};
 use crate::session::Session;
+use crate::time::Rounding;
+use crate::timezone::TimeZone;
 
 thread_local! {
     static STATE: RefCell<Option<GlobalAnalysisState>> = const { RefCell::new(None) };
@@ -21,25 +30,53 @@ thread_local! {
 struct GlobalAnalysisState {
     options: Vec<AnalysisOption>,
     dnfasok: bool,
+    bitmap: bool,
     sessions: Vec<Session>,
+    diagnostics: Vec<ParseDiagnostic>,
     canvas: HtmlCanvasElement,
     parsing_time: Duration,
     analysis_timer: Instant,
     session_times: Vec<(usize, Duration)>,
 }
 
+impl ChartCanvas for HtmlCanvasElement {
+    fn draw(
+        &mut self,
+        session: &Session,
+        chart: &Chart,
+        desc: &str,
+    ) -> Result<ChartImage, Box<dyn std::error::Error>> {
+        let root = CanvasBackend::with_canvas_object(self.clone())
+            .ok_or("Failed to acquire canvas backend")?
+            .into_drawing_area();
+        session.draw_chart(&root, chart, desc)?;
+
+        self.to_data_url()
+            .map(ChartImage::Link)
+            .map_err(|_| "Failed to export canvas as data url".into())
+    }
+}
+
+/// The timezone of the browser, by its IANA name if
+/// supported, or by its current offset from UTC.
+fn browser_timezone() -> TimeZone {
+    let options = DateTimeFormat::new(&Array::new(), &Object::new()).resolved_options();
+
+    Reflect::get(&options, &JsValue::from_str("timeZone"))
+        .ok()
+        .and_then(|name| name.as_string())
+        .and_then(|name| TimeZone::try_from(name.as_str()).ok())
+        .unwrap_or_else(|| {
+            let offset = -Date::new_0().get_timezone_offset() as i32 * 60;
+            FixedOffset::east_opt(offset).map_or_else(TimeZone::default, TimeZone::Fixed)
+        })
+}
+
 /// Converts the markdown content to HTML, a
 /// more time-efficient equivalent to marked.js.
 #[wasm_bindgen]
 pub fn render_markdown(input: &str) -> JsValue {
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_TABLES);
-
-    let parser = Parser::new_ext(input, options);
-    let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
-
-    JsValue::from_str(&html_output)
+    JsValue::from_str(&markdown_to_html(input))
 }
 
 /// Initializes the analysis, parses options and sessions.
@@ -54,16 +91,24 @@ pub fn init_analysis(
 
     rust_i18n::set_locale(locale);
 
-    let options_str = String::from_utf8(options_txt.to_vec())
+    let options_str = std::str::from_utf8(options_txt)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse options: {}", e)))?;
-    let data_str = String::from_utf8(data_txt.to_vec())
+    let data_str = std::str::from_utf8(data_txt)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
 
     let parsing_timer = Instant::now();
 
-    let options = parse_options(&options_str);
+    let options = parse_options(options_str);
+
+    let timezone = options
+        .iter()
+        .find_map(|op| match op {
+            AnalysisOption::TimeZone(timezone) => Some(*timezone),
+            _ => None,
+        })
+        .unwrap_or_else(browser_timezone);
 
-    let sessions = parse_sessions(&data_str);
+    let (sessions, diagnostics) = parse_sessions(data_str, &timezone);
 
     let parsing_time = parsing_timer.elapsed();
     let analysis_timer = Instant::now();
@@ -71,11 +116,25 @@ pub fn init_analysis(
     let dnfasok = options
         .iter()
         .any(|op| matches!(op, AnalysisOption::DnfAsOk));
+    let bitmap = options
+        .iter()
+        .any(|op| matches!(op, AnalysisOption::Bitmap));
+    let rounding = if options.iter().any(|op| matches!(op, AnalysisOption::Wca)) {
+        Rounding::Wca
+    } else {
+        Rounding::Millis
+    };
+    let sessions: Vec<Session> = sessions
+        .into_iter()
+        .map(|s| s.with_rounding(rounding))
+        .collect();
 
     let state = GlobalAnalysisState {
         options,
         dnfasok,
+        bitmap,
         sessions,
+        diagnostics,
         canvas,
         parsing_time,
         analysis_timer,
@@ -98,8 +157,29 @@ pub fn analysis_info() -> Result<JsValue, JsValue> {
         let mut borrow = cell.borrow_mut();
         if let Some(ref mut state) = *borrow {
             let mut chunk = Vec::new();
+            let info = write_analysis_info(
+                &mut chunk,
+                &state.sessions,
+                &state.diagnostics,
+                &state.options,
+            );
+
+            state.options.retain(|op| !op.is_global());
+
+            let info = info.and_then(|empty| {
+                if !empty {
+                    let mut svg_canvas = SvgCanvas;
+                    let canvas: &mut dyn ChartCanvas = if state.bitmap {
+                        &mut state.canvas
+                    } else {
+                        &mut svg_canvas
+                    };
+                    analyze_overall(&state.sessions, &state.options, &mut chunk, canvas)?;
+                }
+                Ok(empty)
+            });
 
-            match write_analysis_info(&mut chunk, &state.sessions, &state.options) {
+            match info {
                 Ok(empty) => match String::from_utf8(chunk) {
                     Ok(mut markdown) => {
                         if empty {
@@ -118,10 +198,6 @@ pub fn analysis_info() -> Result<JsValue, JsValue> {
                     )));
                 }
             }
-
-            state
-                .options
-                .retain(|op| !matches!(op, AnalysisOption::DnfAsOk));
         }
     });
 
@@ -149,14 +225,14 @@ pub fn analyze_nth_session(n: usize) -> Result<JsValue, JsValue> {
 
             let session = &state.sessions[n];
             let mut chunk = Vec::new();
-
-            match analyze_session(
-                session,
-                &state.options,
-                state.dnfasok,
-                &mut chunk,
-                &state.canvas,
-            ) {
+            let mut svg_canvas = SvgCanvas;
+            let canvas: &mut dyn ChartCanvas = if state.bitmap {
+                &mut state.canvas
+            } else {
+                &mut svg_canvas
+            };
+
+            match analyze_session(session, &state.options, state.dnfasok, &mut chunk, canvas) {
                 Ok(duration) => {
                     state.session_times.push((session.rank(), duration));
                     let markdown = String::from_utf8(chunk)
@@ -3,13 +3,111 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::num::ParseIntError;
 
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 
-use crate::time::{AsSeconds, Milliseconds};
+use crate::rolling::CUT_OFF;
+use crate::time::{AsSeconds, HumanReadable, Milliseconds, parse_readable};
+use crate::timezone::{ParseTimeZoneError, TimeZone};
 
 /// The scale of statistics.
 type StatsScale = usize;
 
+/// A percentile, in range 1 to 99.
+pub type Percentile = u8;
+
+/// The unit of calendar buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CalendarUnit {
+    Day,
+    Week,
+    Month,
+}
+
+impl fmt::Display for CalendarUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Day => t!("option.day"),
+            Self::Week => t!("option.week"),
+            Self::Month => t!("option.month"),
+        };
+
+        write!(f, "{}", label)
+    }
+}
+
+impl TryFrom<&str> for CalendarUnit {
+    type Error = ParseAnalysisOptionError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            _ => Err(ParseAnalysisOptionError::InvalidCalendarUnit),
+        }
+    }
+}
+
+impl CalendarUnit {
+    /// The starting date of the bucket which the date is in,
+    /// weeks start on Monday.
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => date - chrono::Days::new(date.weekday().num_days_from_monday() as u64),
+            Self::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Formats the starting date of a bucket.
+    pub fn format_start(&self, start: NaiveDate) -> String {
+        match self {
+            Self::Day | Self::Week => start.format("%Y-%m-%d").to_string(),
+            Self::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// The idle gap in minutes separating sittings, when not specified.
+pub const DEFAULT_SITTING_GAP: u32 = 30;
+
+/// The scope within which solves are positioned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarmupScope {
+    /// Sittings separated by an idle gap in minutes.
+    Sitting(u32),
+
+    /// Calendar days.
+    Day,
+}
+
+impl fmt::Display for WarmupScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Sitting(gap) => t!("option.by-sitting", gap = gap),
+            Self::Day => t!("option.by-day"),
+        };
+
+        write!(f, "{}", label)
+    }
+}
+
+impl TryFrom<&str> for WarmupScope {
+    type Error = ParseAnalysisOptionError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "day" => Ok(Self::Day),
+            gap => gap
+                .parse::<u32>()
+                .ok()
+                .filter(|gap| *gap > 0)
+                .map(Self::Sitting)
+                .ok_or(ParseAnalysisOptionError::InvalidGap),
+        }
+    }
+}
+
 /// The type of statistics.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StatsType {
@@ -23,6 +121,19 @@ pub enum StatsType {
     /// cutting off at least 5% records on both ends,
     /// up to 5% DNFs are allowed.
     Average(StatsScale),
+
+    /// The median of some solves, which is
+    /// DNF only if the middle solves are DNF.
+    Median(StatsScale),
+
+    /// The best of some solves, which is
+    /// DNF only if all solves are DNF.
+    BestOf(StatsScale),
+
+    /// The average of some solves, cutting off the
+    /// specified count of records on both ends,
+    /// up to that count of DNFs are allowed.
+    Trimmed(StatsScale, StatsScale),
 }
 
 impl fmt::Display for StatsType {
@@ -31,6 +142,9 @@ impl fmt::Display for StatsType {
             Self::Single => t!("option.single").to_string(),
             Self::Mean(scale) => format!("mo{}", scale),
             Self::Average(scale) => format!("ao{}", scale),
+            Self::Median(scale) => format!("md{}", scale),
+            Self::BestOf(scale) => format!("bo{}", scale),
+            Self::Trimmed(scale, trim) => format!("ao{}t{}", scale, trim),
         };
 
         write!(f, "{}", label)
@@ -57,6 +171,47 @@ impl TryFrom<&str> for StatsType {
             };
         }
 
+        if let Some(inner) = value.strip_prefix("md") {
+            let scale = inner.parse::<StatsScale>()?;
+
+            return match scale.cmp(&1) {
+                Ordering::Less => Err(ParseStatsTypeError::ScaleIsZero),
+                Ordering::Equal => Ok(Self::Single),
+                Ordering::Greater => Ok(Self::Median(scale)),
+            };
+        }
+
+        if let Some(inner) = value.strip_prefix("bo") {
+            let scale = inner.parse::<StatsScale>()?;
+
+            return match scale.cmp(&1) {
+                Ordering::Less => Err(ParseStatsTypeError::ScaleIsZero),
+                Ordering::Equal => Ok(Self::Single),
+                Ordering::Greater => Ok(Self::BestOf(scale)),
+            };
+        }
+
+        if let Some((scale, trim)) = value
+            .strip_prefix("ao")
+            .and_then(|inner| inner.split_once('t'))
+        {
+            let scale = scale.parse::<StatsScale>()?;
+            let trim = trim.parse::<StatsScale>()?;
+
+            if scale == 0 {
+                return Err(ParseStatsTypeError::ScaleIsZero);
+            }
+            if trim * 2 >= scale {
+                return Err(ParseStatsTypeError::TrimTooLarge);
+            }
+
+            return Ok(match (scale, trim) {
+                (1, _) => Self::Single,
+                (_, 0) => Self::Mean(scale),
+                _ => Self::Trimmed(scale, trim),
+            });
+        }
+
         if let Some(inner) = value.strip_prefix("ao") {
             let scale = inner.parse::<StatsScale>()?;
 
@@ -76,7 +231,26 @@ impl StatsType {
     pub const fn scale(&self) -> StatsScale {
         match self {
             Self::Single => 1,
-            Self::Average(scale) | Self::Mean(scale) => *scale,
+            Self::Average(scale)
+            | Self::Mean(scale)
+            | Self::Median(scale)
+            | Self::BestOf(scale)
+            | Self::Trimmed(scale, _) => *scale,
+        }
+    }
+
+    /// Returns the numbers of the best and the worst
+    /// solves trimmed from a window of the stats type.
+    pub fn trims(&self) -> (usize, usize) {
+        match self {
+            Self::Single | Self::Mean(_) => (0, 0),
+            Self::Average(scale) => {
+                let cut_off = (*scale as f32 * CUT_OFF).ceil() as usize;
+                (cut_off, cut_off)
+            }
+            Self::Median(scale) => ((scale - 1) / 2, (scale - 1) / 2),
+            Self::Trimmed(_, trim) => (*trim, *trim),
+            Self::BestOf(scale) => (0, scale - 1),
         }
     }
 }
@@ -92,6 +266,9 @@ pub enum ParseStatsTypeError {
 
     /// Stats scale is 0.
     ScaleIsZero,
+
+    /// Trimming leaves no solve to average.
+    TrimTooLarge,
 }
 
 impl From<ParseIntError> for ParseStatsTypeError {
@@ -106,6 +283,7 @@ impl fmt::Display for ParseStatsTypeError {
             Self::InvalidFormat => String::from("invalid stats format"),
             Self::InvalidScale(err) => format!("failed to parse int: {}", err),
             Self::ScaleIsZero => String::from("scale must be greater than 0"),
+            Self::TrimTooLarge => String::from("trim must be less than half of the scale"),
         };
 
         write!(f, "{}", literal)
@@ -233,12 +411,77 @@ pub enum AnalysisOption {
     /// Trends of solve times of some stats type.
     Trend(StatsType),
 
+    /// Some percentiles of some stats type, over the
+    /// session and the ranges of `Recent` options.
+    Percentiles(StatsType, Vec<Percentile>),
+
+    /// The current, best and worst stats of some
+    /// stats types, like the stats panel of csTimer.
+    Rolling(Vec<StatsType>),
+
+    /// The best stats of some stats type, up to a count,
+    /// with windows overlapping better ones left out or not.
+    Top(StatsType, usize, bool),
+
+    /// The worst stats of some stats type, up to a count,
+    /// with windows overlapping worse ones left out or not.
+    Worst(StatsType, usize, bool),
+
+    /// Rates of some stats type under a threshold, over the
+    /// session, the ranges of `Recent` options and a rolling window.
+    SubX(StatsType, Milliseconds),
+
+    /// Counts, best, mean and some stats type of solve
+    /// times over every day, week or month.
+    Calendar(StatsType, CalendarUnit),
+
+    /// Sittings, which are runs of solves separated by more
+    /// than some idle gap in minutes, with stats over each.
+    Sittings(u32),
+
+    /// Mean solve times and DNF rates by the position
+    /// of solves within every sitting or day.
+    Warmup(WarmupScope),
+
+    /// Heatmaps of mean solve times and solve
+    /// counts by local weekday and hour.
+    TimeOfDay,
+
+    /// Streaks of consecutive practice days, breaks and the
+    /// volume of practice, also across all sessions.
+    Streaks,
+
+    /// Trends of the standard deviation of solve
+    /// times over windows of some stats type.
+    Consistency(StatsType),
+
     /// Some recent solves specified by a number,
     /// percentage or a range of days.
     Recent(TargetRange),
 
+    /// A comparison of two ranges of solves, with
+    /// tests on the significance of the difference.
+    Compare(TargetRange, TargetRange),
+
     /// `Record`s that has a non-empty comment.
     Commented,
+
+    /// Summary, PB histories and trends of some stats
+    /// type over every phase of multi-phase `Record`s.
+    Phases(StatsType),
+
+    /// Move counts and turns per second of some stats
+    /// type over `Record`s with a smart cube reconstruction.
+    Moves(StatsType),
+
+    /// The timezone to localize date-times by.
+    TimeZone(TimeZone),
+
+    /// Draw charts as bitmap images instead of SVG.
+    Bitmap,
+
+    /// Round solve times and stats as WCA regulations require.
+    Wca,
 }
 
 impl fmt::Display for AnalysisOption {
@@ -259,8 +502,60 @@ impl fmt::Display for AnalysisOption {
                 }
             }
             Self::Trend(s_type) => t!("option.trend", s_type = s_type),
+            Self::Consistency(s_type) => t!("option.consistency", s_type = s_type),
+            Self::Sittings(gap) => t!("option.sittings", gap = gap),
+            Self::Warmup(scope) => t!("option.warmup", scope = scope),
+            Self::Calendar(s_type, unit) => t!("option.calendar", s_type = s_type, unit = unit),
+            Self::SubX(s_type, threshold) => t!(
+                "option.subx",
+                s_type = s_type,
+                threshold = threshold.to_readable_string()
+            ),
+            Self::Top(s_type, count, distinct) => t!(
+                if *distinct {
+                    "option.top-distinct"
+                } else {
+                    "option.top"
+                },
+                s_type = s_type,
+                count = count
+            ),
+            Self::Worst(s_type, count, distinct) => t!(
+                if *distinct {
+                    "option.worst-distinct"
+                } else {
+                    "option.worst"
+                },
+                s_type = s_type,
+                count = count
+            ),
+            Self::Rolling(s_types) => t!(
+                "option.rolling",
+                s_types = s_types
+                    .iter()
+                    .map(|s_type| format!("**{}**", s_type))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Percentiles(s_type, percentiles) => t!(
+                "option.percentiles",
+                s_type = s_type,
+                percentiles = percentiles
+                    .iter()
+                    .map(|p| format!("{}%", p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Self::Recent(range) => t!("option.recent", range = range),
+            Self::Compare(a, b) => t!("option.compare", a = a, b = b),
             Self::Commented => t!("option.commented"),
+            Self::TimeOfDay => t!("option.timeofday"),
+            Self::Streaks => t!("option.streaks"),
+            Self::Phases(s_type) => t!("option.phases", s_type = s_type),
+            Self::Moves(s_type) => t!("option.moves", s_type = s_type),
+            Self::TimeZone(timezone) => t!("option.timezone", timezone = timezone),
+            Self::Bitmap => t!("option.bitmap"),
+            Self::Wca => t!("option.wca"),
         };
 
         write!(f, "{}", label)
@@ -279,6 +574,14 @@ impl TryFrom<&str> for AnalysisOption {
             return Ok(Self::DnfAsOk);
         }
 
+        if value == "bitmap" {
+            return Ok(Self::Bitmap);
+        }
+
+        if value == "wca" {
+            return Ok(Self::Wca);
+        }
+
         if let Some(inner) = value.strip_prefix("pbs(")
             && let Some(inner) = inner.strip_suffix(")")
         {
@@ -309,6 +612,125 @@ impl TryFrom<&str> for AnalysisOption {
             return Ok(Self::Trend(stats));
         }
 
+        if let Some(inner) = value.strip_prefix("percentiles(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let mut splits = inner.split(',');
+            let stats = StatsType::try_from(splits.next().unwrap_or_default())?;
+            let mut percentiles = splits
+                .map(|p| match p.trim().parse::<Percentile>() {
+                    Ok(p) if (1..=99).contains(&p) => Ok(p),
+                    _ => Err(ParseAnalysisOptionError::InvalidPercentile),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if percentiles.is_empty() {
+                percentiles = vec![10, 50, 90];
+            }
+            percentiles.sort_unstable();
+            percentiles.dedup();
+            return Ok(Self::Percentiles(stats, percentiles));
+        }
+
+        if value == "rolling" {
+            return Ok(Self::Rolling(vec![
+                StatsType::Average(5),
+                StatsType::Average(12),
+                StatsType::Average(100),
+            ]));
+        }
+
+        if let Some(inner) = value.strip_prefix("rolling(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let mut s_types = Vec::new();
+            for stats in inner.split(',') {
+                let stats = StatsType::try_from(stats)?;
+                if !s_types.contains(&stats) {
+                    s_types.push(stats);
+                }
+            }
+            return Ok(Self::Rolling(s_types));
+        }
+
+        if let Some(inner) = value.strip_prefix("subx(")
+            && let Some(inner) = inner.strip_suffix(")")
+            && let Some((stats, threshold)) = inner.split_once(',')
+        {
+            let stats = StatsType::try_from(stats)?;
+            let threshold = parse_readable(threshold)
+                .filter(|t| *t > 0)
+                .ok_or(ParseAnalysisOptionError::InvalidThreshold)?;
+            return Ok(Self::SubX(stats, threshold));
+        }
+
+        for (prefix, worst) in [("top(", false), ("worst(", true)] {
+            if let Some(inner) = value.strip_prefix(prefix)
+                && let Some(inner) = inner.strip_suffix(")")
+                && let Some((stats, inner)) = inner.split_once(',')
+            {
+                let stats = StatsType::try_from(stats)?;
+                let (count, distinct) = match inner.split_once(',') {
+                    Some((count, distinct)) if distinct.trim() == "distinct" => (count, true),
+                    Some(_) => return Err(ParseAnalysisOptionError::InvalidFormat),
+                    None => (inner, false),
+                };
+                let count = count
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|count| *count > 0)
+                    .ok_or(ParseAnalysisOptionError::InvalidCount)?;
+
+                return Ok(if worst {
+                    Self::Worst(stats, count, distinct)
+                } else {
+                    Self::Top(stats, count, distinct)
+                });
+            }
+        }
+
+        if let Some(inner) = value.strip_prefix("calendar(")
+            && let Some(inner) = inner.strip_suffix(")")
+            && let Some((stats, unit)) = inner.split_once(',')
+        {
+            let stats = StatsType::try_from(stats)?;
+            let unit = CalendarUnit::try_from(unit)?;
+            return Ok(Self::Calendar(stats, unit));
+        }
+
+        if let Some(inner) = value.strip_prefix("sittings(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let gap = inner
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|gap| *gap > 0)
+                .ok_or(ParseAnalysisOptionError::InvalidGap)?;
+            return Ok(Self::Sittings(gap));
+        }
+
+        if value == "warmup" {
+            return Ok(Self::Warmup(WarmupScope::Sitting(DEFAULT_SITTING_GAP)));
+        }
+
+        if let Some(inner) = value.strip_prefix("warmup(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let scope = WarmupScope::try_from(inner)?;
+            return Ok(Self::Warmup(scope));
+        }
+
+        if let Some(inner) = value.strip_prefix("consistency(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let stats = StatsType::try_from(inner)?;
+            if stats.scale() < 2 {
+                return Err(ParseAnalysisOptionError::SingleNotAllowed);
+            }
+            return Ok(Self::Consistency(stats));
+        }
+
         if let Some(inner) = value.strip_prefix("recent(")
             && let Some(inner) = inner.strip_suffix(")")
         {
@@ -317,19 +739,77 @@ impl TryFrom<&str> for AnalysisOption {
             return Ok(Self::Recent(range));
         }
 
+        if let Some(inner) = value.strip_prefix("compare(")
+            && let Some(inner) = inner.strip_suffix(")")
+            && let Some((a, b)) = inner.split_once(';')
+        {
+            let a = TargetRange::try_from(a).map_err(ParseAnalysisOptionError::InvalidTarget)?;
+            let b = TargetRange::try_from(b).map_err(ParseAnalysisOptionError::InvalidTarget)?;
+            return Ok(Self::Compare(a, b));
+        }
+
         if value == "commented" {
             return Ok(Self::Commented);
         }
 
+        if value == "timeofday" {
+            return Ok(Self::TimeOfDay);
+        }
+
+        if value == "streaks" {
+            return Ok(Self::Streaks);
+        }
+
+        if let Some(inner) = value.strip_prefix("phases(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let stats = StatsType::try_from(inner)?;
+            return Ok(Self::Phases(stats));
+        }
+
+        if let Some(inner) = value.strip_prefix("moves(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let stats = StatsType::try_from(inner)?;
+            return Ok(Self::Moves(stats));
+        }
+
+        if let Some(inner) = value.strip_prefix("timezone(")
+            && let Some(inner) = inner.strip_suffix(")")
+        {
+            let timezone =
+                TimeZone::try_from(inner).map_err(ParseAnalysisOptionError::InvalidTimeZone)?;
+            return Ok(Self::TimeZone(timezone));
+        }
+
         Err(ParseAnalysisOptionError::InvalidFormat)
     }
 }
 
 impl AnalysisOption {
+    /// Returns true if the option configures the whole
+    /// analysis, rather than writing a section.
+    pub const fn is_global(&self) -> bool {
+        matches!(
+            self,
+            Self::DnfAsOk | Self::TimeZone(_) | Self::Bitmap | Self::Wca
+        )
+    }
+
     /// Returns the stats type of the analysis option.
     pub const fn stats_type(&self) -> Option<&StatsType> {
         match self {
-            Self::Pbs(s_type) | Self::Group(s_type, _) | Self::Trend(s_type) => Some(s_type),
+            Self::Pbs(s_type)
+            | Self::Group(s_type, _)
+            | Self::Trend(s_type)
+            | Self::Consistency(s_type)
+            | Self::Percentiles(s_type, _)
+            | Self::SubX(s_type, _)
+            | Self::Top(s_type, _, _)
+            | Self::Worst(s_type, _, _)
+            | Self::Calendar(s_type, _)
+            | Self::Phases(s_type)
+            | Self::Moves(s_type) => Some(s_type),
             _ => None,
         }
     }
@@ -346,6 +826,27 @@ pub enum ParseAnalysisOptionError {
 
     /// Parsing target range failed.
     InvalidTarget(ParseTargetRangeError),
+
+    /// Parsing timezone failed.
+    InvalidTimeZone(ParseTimeZoneError),
+
+    /// The stats type is over a single solve.
+    SingleNotAllowed,
+
+    /// Not a percentile in range 1 to 99.
+    InvalidPercentile,
+
+    /// Not a time like `15.00` or `1:30.00`.
+    InvalidThreshold,
+
+    /// Not one of `day`, `week` or `month`.
+    InvalidCalendarUnit,
+
+    /// Not a number of minutes greater than 0, or `day` for warm-up.
+    InvalidGap,
+
+    /// Not a count greater than 0.
+    InvalidCount,
 }
 
 impl From<ParseStatsTypeError> for ParseAnalysisOptionError {
@@ -360,6 +861,13 @@ impl fmt::Display for ParseAnalysisOptionError {
             Self::InvalidFormat => write!(f, "invalid format"),
             Self::InvalidStats(e) => write!(f, "invalid stats param: {}", e),
             Self::InvalidTarget(e) => write!(f, "invalid target param: {}", e),
+            Self::InvalidTimeZone(e) => write!(f, "invalid timezone param: {}", e),
+            Self::SingleNotAllowed => write!(f, "stats must be over more than 1 solve"),
+            Self::InvalidPercentile => write!(f, "percentile must be in range 1 to 99"),
+            Self::InvalidThreshold => write!(f, "threshold must be a time like 15.00 or 1:30.00"),
+            Self::InvalidCalendarUnit => write!(f, "unit must be one of day, week or month"),
+            Self::InvalidGap => write!(f, "gap must be a number of minutes greater than 0"),
+            Self::InvalidCount => write!(f, "count must be a number greater than 0"),
         }
     }
 }
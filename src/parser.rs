@@ -1,114 +1,503 @@
+use std::fmt;
+
+use serde::Deserializer;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde_json::Value;
-use web_sys::js_sys::Date;
 
+use crate::analyze::plural_form;
 use crate::options::AnalysisOption;
-use crate::record::{Record, SolveState};
+use crate::puzzle::PuzzleType;
+use crate::record::{Reconstruction, Record, SolveState};
 use crate::session::Session;
 use crate::time::Milliseconds;
+use crate::timezone::TimeZone;
+
+/// The count of indices of skipped `Record`s shown per diagnostic.
+const SKIPPED_INDICES_SHOWN: usize = 3;
+
+/// Metadata of a `Session`, which are its id, name,
+/// rank, puzzle type and start and end date-times.
+type SessionMetadata = (usize, String, usize, PuzzleType, (i64, i64));
+
+/// The reason why a `Record` was skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// A field is missing or has a wrong type.
+    InvalidField(&'static str),
 
-// Gets the local offset from UTC in seconds.
-fn local_offset_seconds() -> i64 {
-    -Date::new_0().get_timezone_offset() as i64 * 60
+    /// The penalty code is not one of `0`, `2000` and `-1`.
+    UnknownPenalty(i64),
 }
 
-/// Parses `Session`s and `Record`s within.
-pub fn parse_sessions(input: &str) -> Vec<Session> {
-    let data: Value = match serde_json::from_str(input) {
-        Ok(json) => json,
-        Err(_) => {
-            return Vec::new();
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidField(field) => write!(
+                f,
+                "{}",
+                t!(
+                    "diagnostic.invalid-field",
+                    field = t!(format!("diagnostic.field-{}", field))
+                )
+            ),
+            Self::UnknownPenalty(code) => {
+                write!(f, "{}", t!("diagnostic.unknown-penalty", code = code))
+            }
+        }
+    }
+}
+
+/// A problem found while parsing the data, which causes
+/// some of the data to be left out or filled by defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDiagnostic {
+    /// The data is not valid JSON.
+    InvalidJson {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    /// The data is not a JSON object.
+    NotAnObject,
+
+    /// `properties.sessionData` is not valid JSON.
+    InvalidSessionData(String),
+
+    /// A session is not an array of records, so it was skipped.
+    InvalidSession(usize),
+
+    /// `Record`s of a session were skipped for the same reason,
+    /// with the count and the first few indices (1-based).
+    SkippedRecords {
+        session: usize,
+        reason: SkipReason,
+        count: usize,
+        indices: Vec<usize>,
+    },
+
+    /// A session has no metadata in `properties.sessionData`,
+    /// so a default one was synthesized from its records.
+    MissingMetadata(usize),
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let literal = match self {
+            Self::InvalidJson {
+                line,
+                column,
+                message,
+            } => t!(
+                "diagnostic.invalid-json",
+                line = line,
+                column = column,
+                message = message
+            ),
+            Self::NotAnObject => t!("diagnostic.not-an-object"),
+            Self::InvalidSessionData(message) => {
+                t!("diagnostic.invalid-session-data", message = message)
+            }
+            Self::InvalidSession(session) => {
+                t!("diagnostic.invalid-session", session = session)
+            }
+            Self::SkippedRecords {
+                session,
+                reason,
+                count,
+                indices,
+            } => {
+                let mut indices: Vec<String> =
+                    indices.iter().map(|i| format!("`#{}`", i)).collect();
+                if *count > indices.len() {
+                    indices.push(String::from("..."));
+                }
+                t!(
+                    "diagnostic.skipped-records",
+                    session = session,
+                    count = count,
+                    count_plural = plural_form(*count),
+                    indices = indices.join(", "),
+                    reason = reason
+                )
+            }
+            Self::MissingMetadata(session) => {
+                t!("diagnostic.missing-metadata", session = session)
+            }
+        };
+
+        write!(f, "{}", literal)
+    }
+}
+
+/// Parses `Session`s and `Record`s within, along with
+/// the problems found that caused data to be left out.
+///
+/// Sessions without metadata, as in partial exports or
+/// older versions of csTimer, are named and ranked by
+/// their keys, and dated by their own `Record`s.
+///
+/// Every date-time is localized by the specified `TimeZone`.
+pub fn parse_sessions(input: &str, timezone: &TimeZone) -> (Vec<Session>, Vec<ParseDiagnostic>) {
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    let export = deserializer
+        .deserialize_map(ExportVisitor { timezone })
+        .and_then(|export| deserializer.end().map(|()| export));
+    let (raw_sessions, properties, mut diagnostics) = match export {
+        Ok(export) => export,
+        Err(e) => {
+            // A type error may be reported before the rest of
+            // the input is read, so it's checked to be valid JSON.
+            let e = if e.is_data() {
+                match serde_json::from_str::<IgnoredAny>(input) {
+                    Ok(_) => return (Vec::new(), vec![ParseDiagnostic::NotAnObject]),
+                    Err(e) => e,
+                }
+            } else {
+                e
+            };
+            let diagnostic = ParseDiagnostic::InvalidJson {
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            };
+            return (Vec::new(), vec![diagnostic]);
         }
     };
 
-    let session_metadata = parse_session_metadata(&data);
+    let session_metadata = parse_session_metadata(&properties, &mut diagnostics);
 
     let mut sessions = Vec::new();
-    if let Some(obj) = data.as_object() {
-        let offset = local_offset_seconds();
+    let mut orphans = Vec::new();
 
-        for (key, value) in obj {
-            if key.starts_with("session")
-                && let Some(id) = key
-                    .strip_prefix("session")
-                    .and_then(|id| id.parse::<usize>().ok())
+    for (id, records) in raw_sessions {
+        if records.is_empty() {
+            continue;
+        }
+
+        if let Some((_, name, rank, puzzle, date_time)) =
+            session_metadata.iter().find(|(sid, _, _, _, _)| *sid == id)
+        {
+            sessions.push(Session::from(
+                *rank,
+                if name.is_empty() {
+                    rank.to_string()
+                } else {
+                    name.trim().to_owned()
+                },
+                puzzle.clone(),
+                (
+                    timezone.localize(date_time.0),
+                    timezone.localize(date_time.1),
+                ),
+                records,
+            ));
+        } else {
+            diagnostics.push(ParseDiagnostic::MissingMetadata(id));
+            orphans.push((id, records));
+        }
+    }
+
+    orphans.sort_unstable_by_key(|(id, _)| *id);
+    for (id, records) in orphans {
+        let rank = if sessions.iter().any(|s| s.rank() == id) {
+            sessions.iter().map(Session::rank).max().unwrap_or_default() + 1
+        } else {
+            id
+        };
+        let (start, end) = records
+            .iter()
+            .map(|r| r.date_time().timestamp())
+            .fold((i64::MAX, i64::MIN), |(start, end), t| {
+                (start.min(t), end.max(t))
+            });
+
+        sessions.push(Session::from(
+            rank,
+            rank.to_string(),
+            PuzzleType::default(),
+            (start, end),
+            records,
+        ));
+    }
+
+    sessions.sort_unstable_by_key(super::session::Session::rank);
+
+    (sessions, diagnostics)
+}
+
+/// The contents of an export, which are the `Record`s of
+/// every session by id, the properties that usually come
+/// after the sessions, and the problems found in records.
+type Export = (Vec<(usize, Vec<Record>)>, Value, Vec<ParseDiagnostic>);
+
+/// Visits the top-level object of an export key by key,
+/// so that the whole file is never held as a JSON value.
+struct ExportVisitor<'a> {
+    timezone: &'a TimeZone,
+}
+
+impl<'de> Visitor<'de> for ExportVisitor<'_> {
+    type Value = Export;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a csTimer export")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut sessions = Vec::new();
+        let mut properties = Value::Null;
+        let mut diagnostics = Vec::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            if let Some(id) = key
+                .strip_prefix("session")
+                .and_then(|id| id.parse::<usize>().ok())
             {
-                let records = parse_records(value, offset);
-                if records.is_empty() {
+                let Some((records, skipped)) = map.next_value_seed(RecordsSeed {
+                    timezone: self.timezone,
+                })?
+                else {
+                    diagnostics.push(ParseDiagnostic::InvalidSession(id));
                     continue;
-                }
+                };
+                diagnostics.extend(group_skipped(id, skipped));
+                sessions.push((id, records));
+            } else if key == "properties" {
+                properties = map.next_value()?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        Ok((sessions, properties, diagnostics))
+    }
+}
 
-                if let Some((_, name, rank, date_time)) =
-                    session_metadata.iter().find(|(sid, _, _, _)| *sid == id)
-                {
-                    sessions.push(Session::from(
-                        *rank,
-                        if name.is_empty() {
-                            rank.to_string()
-                        } else {
-                            name.trim().to_owned()
-                        },
-                        (date_time.0 + offset, date_time.1 + offset),
-                        records,
-                    ));
+/// Groups the skipped `Record`s of a session by the reasons,
+/// in order of their first occurrences.
+fn group_skipped(session: usize, skipped: Vec<(usize, SkipReason)>) -> Vec<ParseDiagnostic> {
+    let mut diagnostics: Vec<ParseDiagnostic> = Vec::new();
+
+    for (index, reason) in skipped {
+        let group = diagnostics.iter_mut().find_map(|d| match d {
+            ParseDiagnostic::SkippedRecords {
+                reason: r,
+                count,
+                indices,
+                ..
+            } if *r == reason => Some((count, indices)),
+            _ => None,
+        });
+
+        match group {
+            Some((count, indices)) => {
+                *count += 1;
+                if indices.len() < SKIPPED_INDICES_SHOWN {
+                    indices.push(index);
                 }
             }
+            None => diagnostics.push(ParseDiagnostic::SkippedRecords {
+                session,
+                reason,
+                count: 1,
+                indices: vec![index],
+            }),
         }
     }
 
-    sessions.sort_unstable_by_key(super::session::Session::rank);
+    diagnostics
+}
 
-    sessions
-}
-
-/// Parses `Record`s in a `Session`.
-fn parse_records(session: &Value, offset: i64) -> Vec<Record> {
-    session
-        .as_array()
-        .iter()
-        .next()
-        .unwrap_or(&&Vec::new())
-        .iter()
-        .filter_map(|r| {
-            let mut solve_state = match r.get(0)?.get(0)?.as_i64()? {
-                0 => SolveState::Ok,
-                2000 => SolveState::Plus2,
-                -1 => SolveState::Dnf,
-                _ => return None,
-            };
+/// Streams the `Record`s of a session one at a time, along
+/// with the indexes (1-based) of skipped records and the reasons,
+/// or `None` if the session is not an array.
+struct RecordsSeed<'a> {
+    timezone: &'a TimeZone,
+}
+
+impl<'de> DeserializeSeed<'de> for RecordsSeed<'_> {
+    type Value = Option<(Vec<Record>, Vec<(usize, SkipReason)>)>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RecordsSeed<'_> {
+    type Value = Option<(Vec<Record>, Vec<(usize, SkipReason)>)>;
 
-            let mut time_millis = r.get(0)?.get(1)?.as_i64()?;
-            if time_millis < 0 {
-                time_millis = -time_millis;
-                solve_state = SolveState::Dnf;
-            } else if solve_state.is_plus2() {
-                time_millis += 2000;
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an array of records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut records = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        let mut skipped = Vec::new();
+
+        let mut index = 0;
+        while let Some(r) = seq.next_element::<Value>()? {
+            index += 1;
+            match parse_record(r, self.timezone) {
+                Ok(record) => records.push(record),
+                Err(reason) => skipped.push((index, reason)),
             }
+        }
+
+        Ok(Some((records, skipped)))
+    }
+
+    // Sessions that are not arrays are skipped.
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        IgnoredAny.visit_map(map)?;
+        Ok(Default::default())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Default::default())
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(Default::default())
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+        Ok(Default::default())
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+        Ok(Default::default())
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(Default::default())
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<Self::Value, E> {
+        Ok(Default::default())
+    }
+}
 
-            let scramble = r.get(1)?.as_str()?.to_string();
-            let comment = r
-                .get(2)?
-                .as_str()?
-                .to_string()
-                .trim()
-                .replace("\\\"", "\"")
-                .replace("\\\\", "\\")
-                .replace('*', "\\*");
-            let time_epoch = r.get(3)?.as_i64()?;
-
-            Some(Record::from(
-                solve_state,
-                time_millis as Milliseconds,
-                scramble,
-                comment,
-                time_epoch + offset,
-            ))
+/// Parses a single `Record`, taking the strings out of the value.
+fn parse_record(mut r: Value, timezone: &TimeZone) -> Result<Record, SkipReason> {
+    let times = r.get(0).ok_or(SkipReason::InvalidField("time"))?;
+
+    let penalty = times
+        .get(0)
+        .and_then(Value::as_i64)
+        .ok_or(SkipReason::InvalidField("penalty"))?;
+    let mut solve_state = match penalty {
+        0 => SolveState::Ok,
+        2000 => SolveState::Plus2,
+        -1 => SolveState::Dnf,
+        code => return Err(SkipReason::UnknownPenalty(code)),
+    };
+
+    let mut time_millis = times
+        .get(1)
+        .and_then(Value::as_i64)
+        .ok_or(SkipReason::InvalidField("time"))?;
+    let phases = parse_phases(times, time_millis.abs());
+    if time_millis < 0 {
+        time_millis = -time_millis;
+        solve_state = SolveState::Dnf;
+    } else if solve_state.is_plus2() {
+        time_millis += 2000;
+    }
+
+    let Some(Value::String(scramble)) = r.get_mut(1).map(Value::take) else {
+        return Err(SkipReason::InvalidField("scramble"));
+    };
+    let comment = r
+        .get(2)
+        .and_then(Value::as_str)
+        .ok_or(SkipReason::InvalidField("comment"))?
+        .to_string()
+        .trim()
+        .replace("\\\"", "\"")
+        .replace("\\\\", "\\")
+        .replace('*', "\\*");
+    let time_epoch = r
+        .get(3)
+        .and_then(Value::as_i64)
+        .ok_or(SkipReason::InvalidField("date"))?;
+    let reconstruction = r.get(4).and_then(parse_reconstruction);
+
+    Ok(Record::from(
+        solve_state,
+        time_millis as Milliseconds,
+        scramble,
+        comment,
+        timezone.localize(time_epoch),
+    )
+    .with_phases(phases)
+    .with_reconstruction(reconstruction))
+}
+
+/// Parses per-phase durations of a multi-phase `Record`.
+///
+/// csTimer stores the split points after the total time,
+/// as cumulative times from the last split to the first,
+/// e.g. `[0, total, split_2, split_1]` for 3 phases.
+fn parse_phases(times: &Value, total: i64) -> Vec<Milliseconds> {
+    let Some(splits) = times.as_array().and_then(|t| t.get(2..)) else {
+        return Vec::new();
+    };
+    if splits.is_empty() {
+        return Vec::new();
+    }
+
+    let mut cumulative: Vec<i64> = splits.iter().rev().filter_map(Value::as_i64).collect();
+    if cumulative.len() != splits.len() {
+        return Vec::new();
+    }
+    cumulative.push(total);
+
+    let mut phases = Vec::with_capacity(cumulative.len());
+    let mut last = 0;
+    for split in cumulative {
+        if split < last {
+            return Vec::new();
+        }
+        phases.push((split - last) as Milliseconds);
+        last = split;
+    }
+
+    phases
+}
+
+/// Parses the smart cube reconstruction of a `Record`.
+///
+/// csTimer stores it as `[moves, puzzle]`, where moves are
+/// separated by spaces, each with its timestamp after an `@`,
+/// e.g. `"R@0 U@120 R'@251"`.
+fn parse_reconstruction(value: &Value) -> Option<Reconstruction> {
+    let raw = value
+        .as_str()
+        .or_else(|| value.get(0).and_then(Value::as_str))?;
+
+    let moves: Vec<(String, Milliseconds)> = raw
+        .split_whitespace()
+        .filter_map(|token| match token.split_once('@') {
+            Some((mv, ts)) => Some((mv.to_owned(), ts.parse().ok()?)),
+            None => Some((token.to_owned(), 0)),
         })
-        .collect()
+        .collect();
+
+    if moves.is_empty() {
+        None
+    } else {
+        Some(Reconstruction::from(moves))
+    }
 }
 
 /// Parses metadata for every `Session`.
-fn parse_session_metadata(json: &Value) -> Vec<(usize, String, usize, (i64, i64))> {
-    let session_data = json.get("properties").and_then(|p| p.get("sessionData"));
+fn parse_session_metadata(
+    properties: &Value,
+    diagnostics: &mut Vec<ParseDiagnostic>,
+) -> Vec<SessionMetadata> {
+    let session_data = properties.get("sessionData");
     if session_data.is_none() {
         return Vec::new();
     }
@@ -119,7 +508,8 @@ fn parse_session_metadata(json: &Value) -> Vec<(usize, String, usize, (i64, i64)
         .unwrap_or("{}");
     let data: Value = match serde_json::from_str(data_str) {
         Ok(json) => json,
-        Err(_) => {
+        Err(e) => {
+            diagnostics.push(ParseDiagnostic::InvalidSessionData(e.to_string()));
             return Vec::new();
         }
     };
@@ -138,6 +528,13 @@ fn parse_session_metadata(json: &Value) -> Vec<(usize, String, usize, (i64, i64)
                 .get("rank")
                 .and_then(serde_json::Value::as_u64)
                 .unwrap_or_default() as usize;
+            let puzzle = PuzzleType::from(
+                value
+                    .get("opt")
+                    .and_then(|v| v.get("scrType"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default(),
+            );
 
             let date1 = value
                 .get("date")
@@ -150,7 +547,7 @@ fn parse_session_metadata(json: &Value) -> Vec<(usize, String, usize, (i64, i64)
                 .and_then(serde_json::Value::as_i64)
                 .unwrap_or_default();
 
-            session_data.push((id, name, rank, (date1, date2)));
+            session_data.push((id, name, rank, puzzle, (date1, date2)));
         }
     }
 
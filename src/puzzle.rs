@@ -0,0 +1,121 @@
+use std::fmt;
+
+use crate::time::{HumanReadable, Milliseconds, Seconds};
+
+/// The puzzle type of a session, decided by
+/// the scramble type ("scrType") in csTimer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleType {
+    /// An NxNxN cube.
+    Cube(u8),
+
+    /// 3x3x3 one-handed.
+    OneHanded,
+
+    /// An NxNxN cube blindfolded.
+    Blindfolded(u8),
+
+    /// 3x3x3 fewest moves, times are move counts.
+    FewestMoves,
+
+    /// Clock.
+    Clock,
+
+    /// Megaminx.
+    Megaminx,
+
+    /// Pyraminx.
+    Pyraminx,
+
+    /// Skewb.
+    Skewb,
+
+    /// Square-1.
+    Square1,
+
+    /// Any other scramble type of csTimer.
+    Other(String),
+}
+
+impl Default for PuzzleType {
+    fn default() -> Self {
+        Self::Cube(3)
+    }
+}
+
+impl fmt::Display for PuzzleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cube(n) => write!(f, "{n}x{n}x{n}"),
+            Self::OneHanded => write!(f, "3x3x3 OH"),
+            Self::Blindfolded(n) => write!(f, "{n}x{n}x{n} BLD"),
+            Self::FewestMoves => write!(f, "3x3x3 FMC"),
+            Self::Clock => write!(f, "Clock"),
+            Self::Megaminx => write!(f, "Megaminx"),
+            Self::Pyraminx => write!(f, "Pyraminx"),
+            Self::Skewb => write!(f, "Skewb"),
+            Self::Square1 => write!(f, "Square-1"),
+            Self::Other(scr_type) => write!(f, "{}", scr_type),
+        }
+    }
+}
+
+impl From<&str> for PuzzleType {
+    fn from(scr_type: &str) -> Self {
+        match scr_type {
+            "" | "333" | "333o" | "333ft" => Self::Cube(3),
+            "222so" | "222o" | "2223" => Self::Cube(2),
+            "444wca" | "444o" | "444m" => Self::Cube(4),
+            "555wca" | "555o" => Self::Cube(5),
+            "666wca" | "666si" => Self::Cube(6),
+            "777wca" | "777si" => Self::Cube(7),
+            "333oh" => Self::OneHanded,
+            "333bf" | "333ni" => Self::Blindfolded(3),
+            "444bld" | "444bf" => Self::Blindfolded(4),
+            "555bld" | "555bf" => Self::Blindfolded(5),
+            "333fm" => Self::FewestMoves,
+            "clkwca" | "clko" => Self::Clock,
+            "mgmp" | "mgmo" => Self::Megaminx,
+            "pyrso" | "pyro" => Self::Pyraminx,
+            "skbso" | "skbo" => Self::Skewb,
+            "sqrs" | "sq1h" => Self::Square1,
+            other => Self::Other(other.to_owned()),
+        }
+    }
+}
+
+impl PuzzleType {
+    /// Returns true if times are move counts.
+    pub const fn is_fewest_moves(&self) -> bool {
+        matches!(self, Self::FewestMoves)
+    }
+
+    /// Returns true if the puzzle is solved blindfolded.
+    pub const fn is_blindfolded(&self) -> bool {
+        matches!(self, Self::Blindfolded(_))
+    }
+
+    /// Formats a time, or a move count for FMC,
+    /// where csTimer stores 1 move as 1 second.
+    pub fn format_time(&self, time: Milliseconds) -> String {
+        if self.is_fewest_moves() {
+            if time.is_multiple_of(1000) {
+                format!("{}", time / 1000)
+            } else {
+                format!("{:.2}", time as f32 / 1000.0)
+            }
+        } else {
+            time.to_readable_string()
+        }
+    }
+
+    /// Formats a time in seconds for chart labels,
+    /// or a move count for FMC.
+    pub fn format_seconds(&self, seconds: &Seconds) -> String {
+        if self.is_fewest_moves() {
+            format!("{:.1}", seconds)
+        } else {
+            seconds.to_readable_string()
+        }
+    }
+}
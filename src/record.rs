@@ -2,7 +2,7 @@ use std::fmt;
 
 use chrono::DateTime;
 
-use crate::time::{HumanReadable, Milliseconds};
+use crate::time::{AsSeconds, HumanReadable, Milliseconds};
 
 /// Valid states of a solve, same as the "state" in csTimer.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -46,6 +46,35 @@ impl SolveState {
     }
 }
 
+/// A move-by-move reconstruction of a smart cube solve,
+/// with the timestamp of every move since the start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reconstruction {
+    moves: Vec<(String, Milliseconds)>,
+}
+
+impl Reconstruction {
+    /// Creates a new `Reconstruction` from its moves.
+    pub const fn from(moves: Vec<(String, Milliseconds)>) -> Self {
+        Self { moves }
+    }
+
+    /// The moves and their timestamps of a `Reconstruction`.
+    pub fn moves(&self) -> &[(String, Milliseconds)] {
+        &self.moves
+    }
+
+    /// The number of moves.
+    pub const fn move_count(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// The timestamp of the last move.
+    pub fn duration(&self) -> Milliseconds {
+        self.moves.last().map(|m| m.1).unwrap_or_default()
+    }
+}
+
 /// A cubing record.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Record {
@@ -54,6 +83,8 @@ pub struct Record {
     scramble: String,
     comment: String,
     date_time: i64,
+    phases: Vec<Milliseconds>,
+    reconstruction: Option<Reconstruction>,
 }
 
 impl Record {
@@ -71,9 +102,18 @@ impl Record {
             scramble,
             comment,
             date_time,
+            phases: Vec::new(),
+            reconstruction: None,
         }
     }
 
+    /// Attaches per-phase durations to a `Record`.
+    #[must_use]
+    pub fn with_phases(mut self, phases: Vec<Milliseconds>) -> Self {
+        self.phases = phases;
+        self
+    }
+
     /// The solve state of a `Record`.
     pub const fn solve_state(&self) -> SolveState {
         self.solve_state
@@ -94,54 +134,144 @@ impl Record {
         &self.comment
     }
 
+    /// Attaches a smart cube reconstruction to a `Record`.
+    #[must_use]
+    pub fn with_reconstruction(mut self, reconstruction: Option<Reconstruction>) -> Self {
+        self.reconstruction = reconstruction;
+        self
+    }
+
+    /// The per-phase durations of a `Record`, from the first
+    /// phase to the last, empty if it was timed in one phase.
+    pub fn phases(&self) -> &[Milliseconds] {
+        &self.phases
+    }
+
+    /// The smart cube reconstruction of a `Record`, if any.
+    pub const fn reconstruction(&self) -> Option<&Reconstruction> {
+        self.reconstruction.as_ref()
+    }
+
+    /// The turns per second of a `Record` with a reconstruction,
+    /// timed by its last move, or by the time if unavailable.
+    pub fn tps(&self) -> Option<f32> {
+        let reconstruction = self.reconstruction.as_ref()?;
+        let duration = match reconstruction.duration() {
+            0 => self.time,
+            d => d,
+        };
+
+        if duration == 0 {
+            None
+        } else {
+            Some(reconstruction.move_count() as f32 / duration.as_seconds())
+        }
+    }
+
     /// The date-time of a `Record`, in `chrono::DateTime`.
     pub fn date_time(&self) -> DateTime<chrono::Utc> {
         DateTime::from_timestamp(self.date_time, 0).unwrap_or_default()
     }
 }
 
+/// The details of a `Record` to be displayed,
+/// with times formatted by the formatter.
+pub struct RecordDetail<'a, F: Fn(Milliseconds) -> String> {
+    record: &'a Record,
+    format_time: F,
+}
+
+impl Record {
+    /// The details of a `Record` to be displayed,
+    /// with times formatted by the formatter.
+    pub const fn detail<F: Fn(Milliseconds) -> String>(
+        &self,
+        format_time: F,
+    ) -> RecordDetail<'_, F> {
+        RecordDetail {
+            record: self,
+            format_time,
+        }
+    }
+}
+
 impl fmt::Display for Record {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.detail(|t| t.to_readable_string()))
+    }
+}
+
+impl<F: Fn(Milliseconds) -> String> fmt::Display for RecordDetail<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let record = self.record;
         let cs = t!("colon-space");
-        let solve_state = if self.solve_state.is_ok() {
+        let solve_state = if record.solve_state.is_ok() {
             String::new()
         } else {
-            format!("- {}{cs}**{}**\n", t!("record.state"), self.solve_state)
+            format!("- {}{cs}**{}**\n", t!("record.state"), record.solve_state)
         };
 
-        let scramble = if self.scramble.contains('\n') {
+        let scramble = if record.scramble.contains('\n') {
             format!(
                 r"
 ```
 {}
 ```
 ",
-                self.scramble
+                record.scramble
             )
         } else {
-            format!("<i>{}</i>", self.scramble)
+            format!("<i>{}</i>", record.scramble)
         };
 
-        let comment = if self.comment.is_empty() {
+        let comment = if record.comment.is_empty() {
             String::new()
         } else {
             format!(
                 "- {}{cs}<strong>{}</strong>\n",
                 t!("record.comment"),
-                self.comment
+                record.comment
             )
         };
 
+        let phases = if record.phases.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "- {}{cs}{}\n",
+                t!("record.phases"),
+                record
+                    .phases
+                    .iter()
+                    .map(|p| format!("`{}`", (self.format_time)(*p)))
+                    .collect::<Vec<_>>()
+                    .join(" / ")
+            )
+        };
+
+        let reconstruction = match (&record.reconstruction, record.tps()) {
+            (Some(r), Some(tps)) => format!(
+                "- {}{cs}`{}` (`{:.2}` TPS)\n",
+                t!("record.moves"),
+                r.move_count(),
+                tps
+            ),
+            _ => String::new(),
+        };
+
         write!(
             f,
-            "@{}\n\n{}- {}{cs}`{}`\n- {}{cs}{}\n{}",
-            self.date_time()
+            "@{}\n\n{}- {}{cs}`{}`\n{}{}- {}{cs}{}\n{}",
+            record
+                .date_time()
                 .to_string()
                 .strip_suffix(" UTC")
                 .unwrap_or_default(),
             solve_state,
             t!("record.time"),
-            self.time.to_readable_string(),
+            (self.format_time)(record.time),
+            phases,
+            reconstruction,
             t!("record.scramble"),
             scramble,
             comment
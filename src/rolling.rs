@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::options::StatsType;
+use crate::record::Record;
+use crate::time::{Milliseconds, Rounding};
+
+/// The rate of records cut off on both ends of an average.
+pub const CUT_OFF: f32 = 0.05;
+
+/// Stats of the specified `StatsType` at every position
+/// of the `Record`s, `None` if it's DNF or there are not
+/// enough `Record`s yet, takes O(n log k) for averages.
+pub fn rolling_stats(
+    records: &[Rc<Record>],
+    s_type: &StatsType,
+    dnfasok: bool,
+    rounding: Rounding,
+) -> Vec<Option<Milliseconds>> {
+    let time = |r: &Record| {
+        if r.solve_state().is_dnf() && !dnfasok {
+            None
+        } else {
+            Some(rounding.single(r.time()))
+        }
+    };
+
+    match s_type {
+        // `dnfasok` is only for means and averages, a DNF single is always DNF.
+        StatsType::Single => records
+            .iter()
+            .map(|r| (!r.solve_state().is_dnf()).then(|| rounding.single(r.time())))
+            .collect(),
+
+        StatsType::Mean(s_scale) => {
+            let mut window = MeanWindow::new(rounding);
+
+            records
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    window.insert(time(r));
+                    if i >= *s_scale {
+                        window.remove(time(&records[i - s_scale]));
+                    }
+
+                    (i + 1 >= *s_scale).then(|| window.mean()).flatten()
+                })
+                .collect()
+        }
+
+        StatsType::Average(s_scale) => {
+            let cut_off = (*s_scale as f32 * CUT_OFF).ceil() as usize;
+
+            trimmed_stats(
+                records,
+                *s_scale,
+                TrimmedWindow::new(cut_off, rounding),
+                time,
+            )
+        }
+
+        StatsType::Median(s_scale) => {
+            let window = TrimmedWindow::new((s_scale - 1) / 2, rounding);
+
+            trimmed_stats(records, *s_scale, window, time)
+        }
+
+        StatsType::Trimmed(s_scale, trim) => {
+            trimmed_stats(records, *s_scale, TrimmedWindow::new(*trim, rounding), time)
+        }
+
+        StatsType::BestOf(s_scale) => {
+            let mut window = Multiset::default();
+
+            records
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    if let Some(t) = time(r) {
+                        window.insert(t);
+                    }
+                    if i >= *s_scale
+                        && let Some(t) = time(&records[i - s_scale])
+                    {
+                        window.remove(t);
+                    }
+
+                    (i + 1 >= *s_scale).then(|| window.first()).flatten()
+                })
+                .collect()
+        }
+    }
+}
+
+/// Sample standard deviations of the times in every window
+/// of `s_scale` records, DNFs are left out unless treated as OK,
+/// `None` if there are less than 2 times in the window.
+pub fn rolling_sd(
+    records: &[Rc<Record>],
+    s_scale: usize,
+    dnfasok: bool,
+    rounding: Rounding,
+) -> Vec<Option<Milliseconds>> {
+    let time = |r: &Record| {
+        if r.solve_state().is_dnf() && !dnfasok {
+            None
+        } else {
+            Some(rounding.single(r.time()) as u128)
+        }
+    };
+
+    let (mut sum, mut square_sum, mut count) = (0u128, 0u128, 0u128);
+
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            if let Some(t) = time(r) {
+                sum += t;
+                square_sum += t * t;
+                count += 1;
+            }
+            if i >= s_scale
+                && let Some(t) = time(&records[i - s_scale])
+            {
+                sum -= t;
+                square_sum -= t * t;
+                count -= 1;
+            }
+
+            if i + 1 < s_scale || count < 2 {
+                return None;
+            }
+
+            let variance = (count * square_sum - sum * sum) as f64 / (count * (count - 1)) as f64;
+            Some(variance.sqrt().round() as Milliseconds)
+        })
+        .collect()
+}
+
+/// Averages over every window of `s_scale` records,
+/// slid through by the `TrimmedWindow`.
+fn trimmed_stats(
+    records: &[Rc<Record>],
+    s_scale: usize,
+    mut window: TrimmedWindow,
+    time: impl Fn(&Record) -> Option<Milliseconds>,
+) -> Vec<Option<Milliseconds>> {
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            window.insert(time(r));
+            if i >= s_scale {
+                window.remove(time(&records[i - s_scale]));
+            }
+
+            (i + 1 >= s_scale).then(|| window.average()).flatten()
+        })
+        .collect()
+}
+
+/// A sliding window of times with a running
+/// sum, where DNFs are represented by `None`.
+struct MeanWindow {
+    sum: u64,
+    count: usize,
+    dnf_count: usize,
+    rounding: Rounding,
+}
+
+impl MeanWindow {
+    const fn new(rounding: Rounding) -> Self {
+        Self {
+            sum: 0,
+            count: 0,
+            dnf_count: 0,
+            rounding,
+        }
+    }
+
+    fn insert(&mut self, time: Option<Milliseconds>) {
+        self.count += 1;
+        match time {
+            Some(t) => self.sum += t as u64,
+            None => self.dnf_count += 1,
+        }
+    }
+
+    fn remove(&mut self, time: Option<Milliseconds>) {
+        self.count -= 1;
+        match time {
+            Some(t) => self.sum -= t as u64,
+            None => self.dnf_count -= 1,
+        }
+    }
+
+    fn mean(&self) -> Option<Milliseconds> {
+        (self.dnf_count == 0).then(|| self.rounding.mean(self.sum, self.count))
+    }
+}
+
+/// A multiset of times with a running sum.
+#[derive(Default)]
+struct Multiset {
+    counts: BTreeMap<Milliseconds, usize>,
+    len: usize,
+    sum: u64,
+}
+
+impl Multiset {
+    fn insert(&mut self, time: Milliseconds) {
+        *self.counts.entry(time).or_default() += 1;
+        self.len += 1;
+        self.sum += time as u64;
+    }
+
+    /// Removes a time, returns `false` if it's not in the set.
+    fn remove(&mut self, time: Milliseconds) -> bool {
+        let Some(count) = self.counts.get_mut(&time) else {
+            return false;
+        };
+
+        *count -= 1;
+        if *count == 0 {
+            self.counts.remove(&time);
+        }
+        self.len -= 1;
+        self.sum -= time as u64;
+
+        true
+    }
+
+    fn first(&self) -> Option<Milliseconds> {
+        self.counts.keys().next().copied()
+    }
+
+    fn last(&self) -> Option<Milliseconds> {
+        self.counts.keys().next_back().copied()
+    }
+
+    fn pop_first(&mut self) -> Option<Milliseconds> {
+        let time = self.first()?;
+        self.remove(time);
+
+        Some(time)
+    }
+
+    fn pop_last(&mut self) -> Option<Milliseconds> {
+        let time = self.last()?;
+        self.remove(time);
+
+        Some(time)
+    }
+}
+
+/// A sliding window of times split into the lowest and the
+/// highest `cut_off` times and the middle ones in between,
+/// which keeps the sum of the middle times up to date.
+struct TrimmedWindow {
+    cut_off: usize,
+    low: Multiset,
+    mid: Multiset,
+    high: Multiset,
+    dnf_count: usize,
+    rounding: Rounding,
+}
+
+impl TrimmedWindow {
+    fn new(cut_off: usize, rounding: Rounding) -> Self {
+        Self {
+            cut_off,
+            low: Multiset::default(),
+            mid: Multiset::default(),
+            high: Multiset::default(),
+            dnf_count: 0,
+            rounding,
+        }
+    }
+
+    fn insert(&mut self, time: Option<Milliseconds>) {
+        let t = time.unwrap_or(Milliseconds::MAX);
+        self.dnf_count += usize::from(time.is_none());
+
+        if self.low.last().is_some_and(|last| t < last) {
+            self.low.insert(t);
+        } else if self.high.first().is_some_and(|first| t > first) {
+            self.high.insert(t);
+        } else {
+            self.mid.insert(t);
+        }
+
+        self.rebalance();
+    }
+
+    fn remove(&mut self, time: Option<Milliseconds>) {
+        let t = time.unwrap_or(Milliseconds::MAX);
+        self.dnf_count -= usize::from(time.is_none());
+
+        let _ = self.low.remove(t) || self.mid.remove(t) || self.high.remove(t);
+
+        self.rebalance();
+    }
+
+    /// Moves times between the sets, so that both ends
+    /// hold `cut_off` times if there are enough.
+    fn rebalance(&mut self) {
+        while self.low.len > self.cut_off
+            && let Some(t) = self.low.pop_last()
+        {
+            self.mid.insert(t);
+        }
+        while self.high.len > self.cut_off
+            && let Some(t) = self.high.pop_first()
+        {
+            self.mid.insert(t);
+        }
+        while self.low.len < self.cut_off
+            && let Some(t) = self.mid.pop_first().or_else(|| self.high.pop_first())
+        {
+            self.low.insert(t);
+        }
+        while self.high.len < self.cut_off
+            && let Some(t) = self.mid.pop_last()
+        {
+            self.high.insert(t);
+        }
+    }
+
+    fn average(&self) -> Option<Milliseconds> {
+        if self.mid.len == 0 || self.dnf_count > self.cut_off {
+            None
+        } else {
+            Some(self.rounding.mean(self.mid.sum, self.mid.len))
+        }
+    }
+}
@@ -1,25 +1,42 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::rc::Rc;
 
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDate};
 
-use crate::options::TargetRange;
-use crate::record::Record;
-use crate::time::Milliseconds;
+use crate::options::{CalendarUnit, StatsType, TargetRange};
+use crate::puzzle::PuzzleType;
+use crate::record::{Record, RecordDetail, SolveState};
+use crate::rolling::rolling_stats;
+use crate::time::{Milliseconds, Rounding};
 
 /// A training session, same as the "session" in csTimer.
 #[derive(Debug, Clone)]
 pub struct Session {
     rank: usize,
     name: String,
+    puzzle: PuzzleType,
     date_time: (i64, i64),
     records: Vec<Rc<Record>>,
     records_not_dnf: Vec<Rc<Record>>,
+    rounding: Rounding,
+    rolling_cache: RollingCache,
 }
 
+/// Cached rolling stats of a `Session`, by the
+/// `StatsType` and whether DNFs are treated as OK.
+type RollingCache = RefCell<HashMap<(StatsType, bool), Rc<[Option<Milliseconds>]>>>;
+
 impl Session {
     /// Creates a new `Session` from its fields.
-    pub fn from(rank: usize, name: String, date_time: (i64, i64), records: Vec<Record>) -> Self {
+    pub fn from(
+        rank: usize,
+        name: String,
+        puzzle: PuzzleType,
+        date_time: (i64, i64),
+        records: Vec<Record>,
+    ) -> Self {
         let records: Vec<Rc<Record>> = records.into_iter().map(Rc::new).collect();
         let records_not_dnf = records
             .iter()
@@ -30,12 +47,23 @@ impl Session {
         Self {
             rank,
             name,
+            puzzle,
             date_time,
             records,
             records_not_dnf,
+            rounding: Rounding::default(),
+            rolling_cache: RollingCache::default(),
         }
     }
 
+    /// Sets how solve times and stats are rounded.
+    pub fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self.rolling_cache = RollingCache::default();
+
+        self
+    }
+
     /// Creates a `Session` from an existing one,
     /// which records are within the specified range.
     pub fn try_from_target_range(&self, target_range: &TargetRange) -> Option<Self> {
@@ -45,17 +73,78 @@ impl Session {
             return None;
         }
 
-        let records_not_dnf = records
+        Some(self.with_records(records))
+    }
+
+    /// Creates a `Session` from an existing one, which records
+    /// are grouped into buckets by the local date, labeled by the
+    /// starting date of every bucket, empty buckets are left out.
+    pub fn split_by_calendar(&self, unit: CalendarUnit) -> Vec<(NaiveDate, Self)> {
+        let mut buckets: BTreeMap<NaiveDate, Vec<Rc<Record>>> = BTreeMap::new();
+
+        for record in self.records() {
+            let start = unit.start_of(record.date_time().date_naive());
+            buckets.entry(start).or_default().push(Rc::clone(record));
+        }
+
+        buckets
+            .into_iter()
+            .map(|(start, records)| (start, self.with_records(records)))
+            .collect()
+    }
+
+    /// Creates a `Session` from an existing one, which records
+    /// are the durations of the specified phase (0-based),
+    /// records without such a phase are left out.
+    pub fn try_from_phase(&self, phase: usize) -> Option<Self> {
+        self.try_from_metric(&t!("stats.phase", phase = phase + 1), |r| {
+            r.phases().get(phase).copied()
+        })
+    }
+
+    /// Creates a `Session` from an existing one, which records
+    /// take the value of the specified metric as their time,
+    /// records without such a value are left out.
+    pub fn try_from_metric(
+        &self,
+        label: &str,
+        metric: impl Fn(&Record) -> Option<Milliseconds>,
+    ) -> Option<Self> {
+        let records: Vec<Record> = self
+            .records()
             .iter()
-            .filter(|r| !r.solve_state().is_dnf())
-            .map(Rc::clone)
+            .filter_map(|r| {
+                let time = metric(r)?;
+                let solve_state = if r.solve_state().is_dnf() {
+                    SolveState::Dnf
+                } else {
+                    SolveState::Ok
+                };
+
+                Some(Record::from(
+                    solve_state,
+                    time,
+                    r.scramble().to_owned(),
+                    r.comment().to_owned(),
+                    r.date_time().timestamp(),
+                ))
+            })
             .collect();
 
-        Some(Self {
-            records,
-            records_not_dnf,
-            ..self.clone()
-        })
+        if records.is_empty() {
+            return None;
+        }
+
+        Some(
+            Self::from(
+                self.rank,
+                format!("{} ({})", self.name, label),
+                self.puzzle.clone(),
+                self.date_time,
+                records,
+            )
+            .with_rounding(self.rounding),
+        )
     }
 
     /// The name of a `Session`.
@@ -63,6 +152,34 @@ impl Session {
         &self.name
     }
 
+    /// The puzzle type of a `Session`.
+    pub const fn puzzle(&self) -> &PuzzleType {
+        &self.puzzle
+    }
+
+    /// How solve times and stats of a `Session` are rounded.
+    pub const fn rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    /// Formats a time by the puzzle type and the rounding mode.
+    pub fn format_time(&self, time: Milliseconds) -> String {
+        if self.puzzle.is_fewest_moves() {
+            self.puzzle.format_time(time)
+        } else {
+            self.rounding.format(time)
+        }
+    }
+
+    /// The details of a `Record` to be displayed, with times rounded
+    /// and formatted as singles of the `Session`.
+    pub fn record_detail<'a>(
+        &'a self,
+        record: &'a Record,
+    ) -> RecordDetail<'a, impl Fn(Milliseconds) -> String + 'a> {
+        record.detail(|t| self.format_time(self.rounding.single(t)))
+    }
+
     /// The rank of a `Session`.
     pub const fn rank(&self) -> usize {
         self.rank
@@ -96,15 +213,107 @@ impl Session {
     pub const fn record_not_dnf_count(&self) -> usize {
         self.records_not_dnf.len()
     }
+
+    /// Stats of the specified `StatsType` at every position of
+    /// the `Record`s, computed once and cached for the `Session`.
+    pub fn rolling_stats(&self, s_type: &StatsType, dnfasok: bool) -> Rc<[Option<Milliseconds>]> {
+        let mut cache = self.rolling_cache.borrow_mut();
+
+        Rc::clone(cache.entry((*s_type, dnfasok)).or_insert_with(|| {
+            rolling_stats(self.records(), s_type, dnfasok, self.rounding).into()
+        }))
+    }
+
+    /// The maximum number of phases among the `Record`s.
+    pub fn phase_count(&self) -> usize {
+        self.records()
+            .iter()
+            .map(|r| r.phases().len())
+            .max()
+            .unwrap_or_default()
+    }
 }
 
 impl fmt::Display for Session {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "[#{}] {}", self.rank(), self.name())
+        if self.rank() == 0 {
+            writeln!(f, "{}", self.name())
+        } else {
+            writeln!(f, "[#{}] {}", self.rank(), self.name())
+        }
     }
 }
 
 impl Session {
+    /// Splits a `Session` into sittings, which are runs of `Record`s
+    /// separated by more than the specified idle gap in seconds.
+    pub fn split_by_gap(&self, gap: i64) -> Vec<Self> {
+        let mut sittings: Vec<Vec<Rc<Record>>> = Vec::new();
+        let mut last = None;
+
+        for record in self.records() {
+            let timestamp = record.date_time().timestamp();
+
+            match sittings.last_mut() {
+                Some(sitting) if last.is_some_and(|last| timestamp - last <= gap) => {
+                    sitting.push(Rc::clone(record));
+                }
+                _ => sittings.push(vec![Rc::clone(record)]),
+            }
+            last = Some(timestamp);
+        }
+
+        sittings
+            .into_iter()
+            .map(|records| self.with_records(records))
+            .collect()
+    }
+
+    /// Merges the `Record`s of some `Session`s in order of date-time,
+    /// into a `Session` with the specified name and dated by its
+    /// `Record`s, `None` if there is no `Record` to merge. The merged
+    /// `Session` is ranked 0, which is left out when displayed.
+    pub fn merge(sessions: &[Self], name: String) -> Option<Self> {
+        let first = sessions.first()?;
+        let mut records: Vec<Rc<Record>> = sessions
+            .iter()
+            .flat_map(|s| s.records().iter().map(Rc::clone))
+            .collect();
+        records.sort_by_key(|r| r.date_time());
+        let date_time = (
+            records.first()?.date_time().timestamp(),
+            records.last()?.date_time().timestamp(),
+        );
+
+        Some(Self {
+            rank: 0,
+            name,
+            puzzle: PuzzleType::default(),
+            date_time,
+            ..first.with_records(records)
+        })
+    }
+
+    /// A `Session` like this one, with the specified `Record`s.
+    fn with_records(&self, records: Vec<Rc<Record>>) -> Self {
+        let records_not_dnf = records
+            .iter()
+            .filter(|r| !r.solve_state().is_dnf())
+            .map(Rc::clone)
+            .collect();
+
+        Self {
+            rank: self.rank,
+            name: self.name.clone(),
+            puzzle: self.puzzle.clone(),
+            date_time: self.date_time,
+            records,
+            records_not_dnf,
+            rounding: self.rounding,
+            rolling_cache: RollingCache::default(),
+        }
+    }
+
     /// The `Record`s within the specified `TargetRange`.
     fn records_in_target_range(&self, target_range: &TargetRange) -> Vec<Rc<Record>> {
         match target_range {
@@ -136,6 +345,13 @@ impl Session {
     }
 }
 
+/// A point of a trend, with the 1-based index of a `Record`
+/// and the value there, `None` if it's DNF or not available.
+pub type TrendPoint = (usize, Option<u32>);
+
+/// A row of heatmap cells, where `None` is an empty cell.
+pub type HeatmapRow = Vec<Option<u32>>;
+
 /// A group of `Record`s, with a starting time
 /// and a count of the `Record`s.
 pub type GroupTime = (Milliseconds, usize);
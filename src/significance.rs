@@ -0,0 +1,217 @@
+use std::f64::consts::SQRT_2;
+
+/// The statistic, the degrees of freedom, the two-sided p-value
+/// and the 95% confidence interval of the difference in mean.
+pub type WelchTest = (f64, f64, f64, (f64, f64));
+
+/// The U statistic of the second sample, the z-score
+/// (positive if the second sample tends to be larger)
+/// and the two-sided p-value.
+pub type MannWhitneyTest = (f64, f64, f64);
+
+/// The significance level of two-sided tests.
+pub const ALPHA: f64 = 0.05;
+
+/// Welch's t-test on the difference in mean of two samples (the
+/// second minus the first), `None` if either sample has less than
+/// 2 values or both samples have no variance.
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Option<WelchTest> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+
+    let mean_and_var = |xs: &[f64]| {
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (n, mean, var)
+    };
+    let (na, mean_a, var_a) = mean_and_var(a);
+    let (nb, mean_b, var_b) = mean_and_var(b);
+
+    let (se_a, se_b) = (var_a / na, var_b / nb);
+    let se = (se_a + se_b).sqrt();
+    if se == 0.0 {
+        return None;
+    }
+
+    let diff = mean_b - mean_a;
+    let t = diff / se;
+    let df = (se_a + se_b).powi(2) / (se_a.powi(2) / (na - 1.0) + se_b.powi(2) / (nb - 1.0));
+    let p = t_two_sided_p(t, df);
+    let margin = t_critical(df) * se;
+
+    Some((t, df, p, (diff - margin, diff + margin)))
+}
+
+/// Mann-Whitney U test on two samples, by the normal approximation
+/// with tie and continuity corrections, `None` if either sample is
+/// empty or every value is the same.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<MannWhitneyTest> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let mut values: Vec<(f64, bool)> = a
+        .iter()
+        .map(|x| (*x, false))
+        .chain(b.iter().map(|x| (*x, true)))
+        .collect();
+    values.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // ranks of ties are averaged, and counted for the correction
+    let (mut rank_sum, mut ties) = (0.0, 0.0);
+    let mut i = 0;
+    while i < values.len() {
+        let j = i + values[i..]
+            .iter()
+            .take_while(|v| v.0 == values[i].0)
+            .count();
+        let rank = (i + j + 1) as f64 / 2.0;
+        let tied = (j - i) as f64;
+
+        rank_sum += rank * values[i..j].iter().filter(|v| v.1).count() as f64;
+        ties += tied.powi(3) - tied;
+        i = j;
+    }
+
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let n = na + nb;
+    let u = rank_sum - nb * (nb + 1.0) / 2.0;
+    let mu = na * nb / 2.0;
+    let sigma = (na * nb / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)))).sqrt();
+    if sigma.is_nan() || sigma == 0.0 {
+        return None;
+    }
+
+    let z = (u - mu).signum() * ((u - mu).abs() - 0.5).max(0.0) / sigma;
+    let p = erfc(z.abs() / SQRT_2).min(1.0);
+
+    Some((u, z, p))
+}
+
+/// The two-sided p-value of Student's t-distribution.
+fn t_two_sided_p(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// The two-sided critical value of Student's
+/// t-distribution at the significance level.
+fn t_critical(df: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1e6);
+
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if t_two_sided_p(mid, df) > ALPHA {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+/// The natural logarithm of the gamma function, by
+/// the Lanczos approximation for positive values.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    // the continued fraction converges quickly on this side
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// The continued fraction of the incomplete beta
+/// function, by the modified Lentz's method.
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut fraction = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        fraction *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        fraction *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    fraction
+}
+
+/// The complementary error function, by a Chebyshev
+/// approximation with a relative error under 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, c| acc * t + c);
+    let result = t * (-z * z + poly).exp();
+
+    if x >= 0.0 { result } else { 2.0 - result }
+}
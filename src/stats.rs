@@ -1,17 +1,18 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
 use std::rc::Rc;
 
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate, Timelike};
+use plotters::coord::Shift;
 use plotters::prelude::*;
-use plotters_canvas::CanvasBackend;
-use web_sys::HtmlCanvasElement;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
 
-use crate::options::StatsType;
+use crate::chart::{Chart, Overlay, TrendScale};
+use crate::options::{CalendarUnit, Percentile, StatsType, WarmupScope};
 use crate::record::{Record, SolveState};
-use crate::session::{GroupTime, Session};
-use crate::time::{AsSeconds, HumanReadable, Milliseconds, Seconds};
-
-const CUT_OFF: f32 = 0.05;
+use crate::rolling::rolling_sd;
+use crate::session::{GroupTime, HeatmapRow, Session, TrendPoint};
+use crate::time::{AsSeconds, Milliseconds};
 
 const MARGIN: i32 = 20;
 const SPACING_RATE: f32 = 0.05;
@@ -24,11 +25,84 @@ const LABEL_FONT_SIZE: i32 = 32;
 
 const MONOSPACE: &str = "JetBrains Mono, Consolas, Courier New, monospace";
 const PLOT_COLOR: RGBColor = RGBColor(91, 169, 253);
+const MARKER_COLOR: RGBColor = RGBColor(240, 96, 96);
+const OVERLAY_COLOR: RGBColor = RGBColor(250, 160, 60);
+const HEAT_LOW_COLOR: RGBColor = RGBColor(232, 243, 255);
+const HEAT_HIGH_COLOR: RGBColor = RGBColor(16, 82, 168);
+const HEAT_EMPTY_COLOR: RGBColor = RGBColor(242, 242, 242);
+
+/// The standard deviation, coefficient of variation (in percentage),
+/// interquartile range and median absolute deviation of solve times.
+pub type Dispersion = (Milliseconds, f32, Milliseconds, Milliseconds);
+
+/// Practice streaks and volume of a `Session`, by local dates.
+pub struct Streaks {
+    /// The starting date and the length in days
+    /// of the longest run of consecutive practice days.
+    pub longest_streak: (NaiveDate, usize),
+
+    /// The length in days of the run of consecutive
+    /// practice days ending on the last practice day.
+    pub current_streak: usize,
+
+    /// The first idle date and the length in days of the longest
+    /// break between practice days, `None` if there is no break.
+    pub longest_break: Option<(NaiveDate, usize)>,
+
+    /// The count of days with at least a solve.
+    pub active_days: usize,
+
+    /// The most solves done in a single day.
+    pub solves_max: usize,
+
+    /// The total time spent solving, DNFs included.
+    pub total_time: u64,
+
+    /// The count of solves in every week (starting on Monday)
+    /// from the first practice day to the last, labeled by the
+    /// starting date of the week.
+    pub weekly_solves: Vec<(NaiveDate, usize)>,
+}
+
+/// The current stats, the position (1-based) and value of the best
+/// stats, and the position and value of the worst stats, where
+/// `None` is DNF.
+pub type RollingSummary = (
+    Option<Milliseconds>,
+    Option<(usize, Milliseconds)>,
+    (usize, Option<Milliseconds>),
+);
+
+/// Picks ranked stats up to the count, leaving out windows of the
+/// scale overlapping picked ones if distinct.
+fn pick_stats(
+    ranked: Vec<(usize, Option<Milliseconds>)>,
+    scale: usize,
+    count: usize,
+    distinct: bool,
+) -> Vec<(usize, Option<Milliseconds>)> {
+    let mut picked: Vec<(usize, Option<Milliseconds>)> = Vec::new();
+
+    for (i, t) in ranked {
+        if picked.len() >= count {
+            break;
+        }
+        if distinct && picked.iter().any(|(p, _)| p.abs_diff(i) < scale) {
+            continue;
+        }
+        picked.push((i, t));
+    }
+
+    picked
+}
+
+/// The quantile of sorted values, interpolated
+/// linearly between the closest ranks.
+fn quantile(sorted: &[Milliseconds], q: f32) -> f32 {
+    let rank = (sorted.len() - 1) as f32 * q;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
 
-/// The plain arithmetic mean over a sum of
-/// Milliseconds, rounds at 1 millis.
-fn round_mean(sum: Milliseconds, count: usize) -> Milliseconds {
-    (sum as f32 / count as f32).round() as Milliseconds
+    sorted[lower] as f32 + (sorted[upper] as f32 - sorted[lower] as f32) * (rank - lower as f32)
 }
 
 impl Session {
@@ -36,7 +110,7 @@ impl Session {
     fn best_and_worst(&self) -> (Milliseconds, Milliseconds) {
         self.records_not_dnf()
             .iter()
-            .map(|r| r.time())
+            .map(|r| self.rounding().single(r.time()))
             .fold((u32::MAX, u32::MIN), |(best, worst), time| {
                 (best.min(time), worst.max(time))
             })
@@ -49,9 +123,57 @@ impl Session {
         } else {
             self.records_not_dnf()
         };
-        let sum: Milliseconds = iter.iter().map(|r| r.time()).sum();
+        let sum: u64 = iter
+            .iter()
+            .map(|r| self.rounding().single(r.time()) as u64)
+            .sum();
+
+        self.rounding().mean(sum, iter.len())
+    }
+
+    /// Dispersion of solve times that are not DNF, `None`
+    /// if there are less than 2 such solve times.
+    fn dispersion(&self, dnfasok: bool) -> Option<Dispersion> {
+        let iter = if dnfasok {
+            self.records()
+        } else {
+            self.records_not_dnf()
+        };
+        let mut times: Vec<Milliseconds> = iter
+            .iter()
+            .map(|r| self.rounding().single(r.time()))
+            .collect();
+
+        if times.len() < 2 {
+            return None;
+        }
 
-        round_mean(sum, iter.len())
+        times.sort_unstable();
+        let count = times.len() as f64;
+        let mean = times.iter().map(|t| *t as f64).sum::<f64>() / count;
+        let variance = times
+            .iter()
+            .map(|t| (*t as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (count - 1.0);
+        let sd = variance.sqrt();
+        let cv = (sd / mean * 100.0) as f32;
+        let iqr = quantile(&times, 0.75) - quantile(&times, 0.25);
+
+        let median = quantile(&times, 0.5);
+        let mut deviations: Vec<Milliseconds> = times
+            .iter()
+            .map(|t| (*t as f32 - median).abs().round() as Milliseconds)
+            .collect();
+        deviations.sort_unstable();
+        let mad = quantile(&deviations, 0.5);
+
+        Some((
+            sd.round() as Milliseconds,
+            cv,
+            iqr.round() as Milliseconds,
+            mad.round() as Milliseconds,
+        ))
     }
 
     /// Count of `Record`s with the specified `SolveState`.
@@ -64,67 +186,15 @@ impl Session {
 
     /// Stats a single solve, or the mean or average over some solves.
     fn stats(&self, pos: usize, s_type: &StatsType, dnfasok: bool) -> Option<Milliseconds> {
-        match s_type {
-            StatsType::Single => {
-                let r = &self.records()[pos];
-
-                if r.solve_state().is_dnf() {
-                    None
-                } else {
-                    Some(r.time())
-                }
-            }
-
-            StatsType::Mean(s_scale) => {
-                let chunk = &self.records()[pos + 1 - s_scale..=pos];
-
-                if chunk.iter().any(|r| r.solve_state().is_dnf() && !dnfasok) {
-                    None
-                } else {
-                    Some(round_mean(chunk.iter().map(|r| r.time()).sum(), *s_scale))
-                }
-            }
-
-            StatsType::Average(s_scale) => {
-                let chunk = &self.records()[pos + 1 - s_scale..=pos];
-                let cut_off = (*s_scale as f32 * CUT_OFF).ceil() as usize;
-                let take = s_scale.saturating_sub(cut_off * 2);
-
-                if take == 0
-                    || chunk
-                        .iter()
-                        .filter(|r| r.solve_state().is_dnf() && !dnfasok)
-                        .count()
-                        > cut_off
-                {
-                    None
-                } else {
-                    let mut chunk: Vec<Milliseconds> = chunk
-                        .iter()
-                        .map(|r| {
-                            if r.solve_state().is_dnf() && !dnfasok {
-                                u32::MAX
-                            } else {
-                                r.time()
-                            }
-                        })
-                        .collect();
-                    chunk.sort_unstable();
-
-                    Some(round_mean(
-                        chunk.iter().skip(cut_off).take(take).sum(),
-                        take,
-                    ))
-                }
-            }
-        }
+        self.rolling_stats(s_type, dnfasok)[pos]
     }
 
     /// All non-DNF data of the specified `StatsType` over the `Session`.
     fn stats_data(&self, s_type: &StatsType, dnfasok: bool) -> Vec<Milliseconds> {
-        (0..self.record_count())
-            .skip(s_type.scale() - 1)
-            .filter_map(|i| self.stats(i, s_type, dnfasok))
+        self.rolling_stats(s_type, dnfasok)
+            .iter()
+            .flatten()
+            .copied()
             .collect()
     }
 }
@@ -139,8 +209,61 @@ impl Session {
             .len()
     }
 
+    /// Practice streaks and volume of the `Session`, `None`
+    /// if there is no `Record`.
+    pub fn streaks(&self) -> Option<Streaks> {
+        let mut solves_by_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        for r in self.records() {
+            *solves_by_day.entry(r.date_time().date_naive()).or_default() += 1;
+        }
+
+        let (first, last) = (
+            *solves_by_day.keys().next()?,
+            *solves_by_day.keys().next_back()?,
+        );
+        let mut longest_streak = (first, 1);
+        let mut current_streak = (first, 1);
+        let mut longest_break: Option<(NaiveDate, usize)> = None;
+        for (prev, day) in solves_by_day.keys().zip(solves_by_day.keys().skip(1)) {
+            let gap = day.signed_duration_since(*prev).num_days() as usize;
+
+            if gap == 1 {
+                current_streak.1 += 1;
+            } else {
+                current_streak = (*day, 1);
+                if longest_break.is_none_or(|b| gap - 1 > b.1) {
+                    longest_break = Some((*prev + chrono::Days::new(1), gap - 1));
+                }
+            }
+            if current_streak.1 > longest_streak.1 {
+                longest_streak = current_streak;
+            }
+        }
+
+        let mut weekly_solves = Vec::new();
+        let mut week = CalendarUnit::Week.start_of(first);
+        while week <= last {
+            let next = week + chrono::Days::new(7);
+            let solves = solves_by_day.range(week..next).map(|(_, n)| n).sum();
+            weekly_solves.push((week, solves));
+            week = next;
+        }
+
+        Some(Streaks {
+            longest_streak,
+            current_streak: current_streak.1,
+            longest_break,
+            active_days: solves_by_day.len(),
+            solves_max: solves_by_day.values().copied().max().unwrap_or_default(),
+            total_time: self.records().iter().map(|r| r.time() as u64).sum(),
+            weekly_solves,
+        })
+    }
+
     /// The best, worst, mean and average solve times of the `Session`,
-    /// where the average could be DNF represented by `None`.
+    /// where the average could be DNF represented by `None`, BLD
+    /// sessions take the best mo3 in place of the average, along
+    /// with the dispersion of solve times.
     pub fn summary(
         &self,
         dnfasok: bool,
@@ -149,13 +272,20 @@ impl Session {
         Milliseconds,
         Milliseconds,
         Option<Milliseconds>,
+        Option<Dispersion>,
     ) {
         let record_count = self.record_count();
         let (best, worst) = self.best_and_worst();
         let mean = self.mean(dnfasok);
-        let average = self.stats(record_count - 1, &StatsType::Average(record_count), dnfasok);
+        let average = if self.puzzle().is_blindfolded() {
+            self.stats_data(&StatsType::Mean(3), dnfasok)
+                .into_iter()
+                .min()
+        } else {
+            self.stats(record_count - 1, &StatsType::Average(record_count), dnfasok)
+        };
 
-        (best, worst, mean, average)
+        (best, worst, mean, average, self.dispersion(dnfasok))
     }
 
     /// The counts of solve states of the `Session`.
@@ -170,12 +300,12 @@ impl Session {
     /// `Record`s that breaked the personal best of the
     /// specified `StatsType`, with its index and the new PB.
     pub fn pbs(&self, s_type: &StatsType, dnfasok: bool) -> Vec<(usize, Milliseconds, Rc<Record>)> {
-        let s_scale = s_type.scale();
+        let data = self.rolling_stats(s_type, dnfasok);
         let mut pb = u32::MAX;
         let mut pbs = Vec::new();
 
-        for (i, record) in self.records().iter().enumerate().skip(s_scale - 1) {
-            if let Some(stats) = self.stats(i, s_type, dnfasok)
+        for (i, record) in self.records().iter().enumerate() {
+            if let Some(stats) = data[i]
                 && stats < pb
             {
                 pb = stats;
@@ -186,19 +316,59 @@ impl Session {
         pbs
     }
 
+    /// The `Record`s of the window of the specified stats ending at
+    /// the position (0-based), with their 1-based indices and whether
+    /// they are trimmed from the stats, where DNFs are the worst.
+    pub fn stats_window(
+        &self,
+        end: usize,
+        s_type: &StatsType,
+        dnfasok: bool,
+    ) -> Vec<(usize, Rc<Record>, bool)> {
+        let start = (end + 1).saturating_sub(s_type.scale());
+        let records = &self.records()[start..=end];
+        let dnfasok = dnfasok && !matches!(s_type, StatsType::Single);
+        let time = |r: &Record| {
+            if r.solve_state().is_dnf() && !dnfasok {
+                None
+            } else {
+                Some(self.rounding().single(r.time()))
+            }
+        };
+
+        let mut order: Vec<usize> = (0..records.len()).collect();
+        order.sort_by_key(|i| time(&records[*i]).unwrap_or(Milliseconds::MAX));
+        let (best, worst) = s_type.trims();
+        let mut trimmed = vec![false; records.len()];
+        for i in order
+            .iter()
+            .take(best)
+            .chain(order.iter().rev().take(worst))
+        {
+            trimmed[*i] = true;
+        }
+
+        records
+            .iter()
+            .zip(trimmed)
+            .enumerate()
+            .map(|(i, (r, trimmed))| (start + i + 1, Rc::clone(r), trimmed))
+            .collect()
+    }
+
     /// A trend of time of pbs over solves.
-    pub fn pbs_trends(&self, pbs: &[(usize, Milliseconds, Rc<Record>)]) -> Vec<(usize, u32)> {
+    pub fn pbs_trends(&self, pbs: &[(usize, Milliseconds, Rc<Record>)]) -> Vec<TrendPoint> {
         let n = self.record_count();
-        let mut trends: Vec<(usize, u32)> = (0..n).map(|i| (i + 1, 0)).collect();
+        let mut trends: Vec<TrendPoint> = (0..n).map(|i| (i + 1, None)).collect();
 
         pbs.windows(2).for_each(|w| {
             let (start, pb) = (w[0].0, w[0].1);
             let end = w[1].0;
-            trends[start..end].iter_mut().for_each(|p| p.1 = pb);
+            trends[start..end].iter_mut().for_each(|p| p.1 = Some(pb));
         });
 
         if let Some(&(last, pb, _)) = pbs.last() {
-            trends[last..n].iter_mut().for_each(|p| p.1 = pb);
+            trends[last..n].iter_mut().for_each(|p| p.1 = Some(pb));
         }
 
         trends
@@ -260,39 +430,335 @@ impl Session {
     }
 
     /// A trend of time of the specified type over solves.
-    pub fn trend(&self, s_type: &StatsType, dnfasok: bool) -> Vec<(usize, u32)> {
-        let s_scale = s_type.scale();
-        let mut trends: Vec<(usize, u32)> = (0..self.record_count()).map(|i| (i + 1, 0)).collect();
+    pub fn trend(&self, s_type: &StatsType, dnfasok: bool) -> Vec<TrendPoint> {
+        self.rolling_stats(s_type, dnfasok)
+            .iter()
+            .enumerate()
+            .map(|(i, stats)| (i + 1, *stats))
+            .collect()
+    }
+
+    /// The specified percentiles of the specified `StatsType` by the
+    /// nearest rank, where DNFs rank last and are represented by `None`.
+    pub fn percentiles(
+        &self,
+        s_type: &StatsType,
+        dnfasok: bool,
+        percentiles: &[Percentile],
+    ) -> Vec<Option<Milliseconds>> {
+        let mut data: Vec<Milliseconds> = self
+            .rolling_stats(s_type, dnfasok)
+            .iter()
+            .skip(s_type.scale() - 1)
+            .map(|stats| stats.unwrap_or(Milliseconds::MAX))
+            .collect();
+        data.sort_unstable();
 
-        for (i, _) in self.records().iter().enumerate().skip(s_scale - 1) {
-            trends[i].1 = self.stats(i, s_type, dnfasok).unwrap_or_default();
+        percentiles
+            .iter()
+            .map(|p| {
+                let rank = (data.len() * *p as usize).div_ceil(100).max(1);
+                data.get(rank - 1).copied()
+            })
+            .map(|stats| stats.filter(|t| *t != Milliseconds::MAX))
+            .collect()
+    }
+
+    /// The mean of all non-DNF data of the specified `StatsType`
+    /// over the `Session`, `None` if there is no such data.
+    pub fn stats_mean(&self, s_type: &StatsType, dnfasok: bool) -> Option<Milliseconds> {
+        let data = self.stats_data(s_type, dnfasok);
+        let sum: u64 = data.iter().map(|t| *t as u64).sum();
+
+        (!data.is_empty()).then(|| self.rounding().mean(sum, data.len()))
+    }
+
+    /// Stats of solves by their position within every sitting or
+    /// day, as the count of solves, the count of DNFs and the mean
+    /// of solve times (`None` if every solve is DNF) at each position.
+    pub fn warmup(
+        &self,
+        scope: &WarmupScope,
+        dnfasok: bool,
+    ) -> Vec<(usize, usize, Option<Milliseconds>)> {
+        let parts: Vec<Session> = match scope {
+            WarmupScope::Sitting(gap) => self.split_by_gap(*gap as i64 * 60),
+            WarmupScope::Day => self
+                .split_by_calendar(CalendarUnit::Day)
+                .into_iter()
+                .map(|(_, part)| part)
+                .collect(),
+        };
+        // count, DNF count, sum and count of times in the mean
+        let mut positions: Vec<(usize, usize, u64, usize)> = Vec::new();
+
+        for part in &parts {
+            for (i, r) in part.records().iter().enumerate() {
+                if positions.len() <= i {
+                    positions.push((0, 0, 0, 0));
+                }
+                let position = &mut positions[i];
+                let is_dnf = r.solve_state().is_dnf();
+
+                position.0 += 1;
+                if is_dnf {
+                    position.1 += 1;
+                }
+                if !is_dnf || dnfasok {
+                    position.2 += self.rounding().single(r.time()) as u64;
+                    position.3 += 1;
+                }
+            }
         }
 
-        trends
+        positions
+            .into_iter()
+            .map(|(count, dnf_count, sum, mean_count)| {
+                let mean = (mean_count > 0).then(|| self.rounding().mean(sum, mean_count));
+                (count, dnf_count, mean)
+            })
+            .collect()
+    }
+
+    /// The count of solves and the mean of solve times (`None`
+    /// if every solve is DNF) by local weekday and hour, as rows
+    /// of weekdays from Monday with a column for every hour.
+    pub fn time_of_day(&self, dnfasok: bool) -> Vec<Vec<(usize, Option<Milliseconds>)>> {
+        // count, sum and count of times in the mean
+        let mut cells = vec![[(0usize, 0u64, 0usize); 24]; 7];
+
+        for r in self.records() {
+            let date_time = r.date_time();
+            let cell = &mut cells[date_time.weekday().num_days_from_monday() as usize]
+                [date_time.hour() as usize];
+
+            cell.0 += 1;
+            if !r.solve_state().is_dnf() || dnfasok {
+                cell.1 += self.rounding().single(r.time()) as u64;
+                cell.2 += 1;
+            }
+        }
+
+        cells
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|(count, sum, mean_count)| {
+                        let mean = (mean_count > 0).then(|| self.rounding().mean(sum, mean_count));
+                        (count, mean)
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
-    /// Draws an image on canvas, visualizes grouping results.
-    pub fn draw_grouping(
+    /// The positions (0-based) and values of the best stats of the
+    /// specified type up to the count, from the best, where the earlier
+    /// is taken among equal stats, and windows overlapping better ones
+    /// are left out if distinct.
+    pub fn top_stats(
         &self,
-        canvas: &HtmlCanvasElement,
+        s_type: &StatsType,
+        dnfasok: bool,
+        count: usize,
+        distinct: bool,
+    ) -> Vec<(usize, Option<Milliseconds>)> {
+        let mut ranked = self.ranked_candidates(s_type, dnfasok);
+        ranked.retain(|(_, t)| t.is_some());
+        ranked.sort_by_key(|(_, t)| *t);
+
+        pick_stats(ranked, s_type.scale(), count, distinct)
+    }
+
+    /// The positions (0-based) and values of the worst stats of the
+    /// specified type up to the count, from the worst, where DNF is the
+    /// worst and the earlier is taken among equal stats, and windows
+    /// overlapping worse ones are left out if distinct.
+    pub fn worst_stats(
+        &self,
+        s_type: &StatsType,
+        dnfasok: bool,
+        count: usize,
+        distinct: bool,
+    ) -> Vec<(usize, Option<Milliseconds>)> {
+        let mut ranked = self.ranked_candidates(s_type, dnfasok);
+        ranked.sort_by_key(|(_, t)| Reverse(t.unwrap_or(Milliseconds::MAX)));
+
+        pick_stats(ranked, s_type.scale(), count, distinct)
+    }
+
+    /// The positions (0-based) and values of the stats
+    /// of the specified type with enough solves.
+    fn ranked_candidates(
+        &self,
+        s_type: &StatsType,
+        dnfasok: bool,
+    ) -> Vec<(usize, Option<Milliseconds>)> {
+        (s_type.scale().saturating_sub(1)..self.record_count())
+            .map(|i| (i, self.stats(i, s_type, dnfasok)))
+            .collect()
+    }
+
+    /// The current, best and worst stats of the specified type, where
+    /// the earliest is taken among equal stats and DNF is the worst,
+    /// `None` if there are not enough solves.
+    pub fn rolling_summary(&self, s_type: &StatsType, dnfasok: bool) -> Option<RollingSummary> {
+        let record_count = self.record_count();
+        let first = s_type.scale().checked_sub(1)?;
+        if record_count <= first {
+            return None;
+        }
+
+        let current = self.stats(record_count - 1, s_type, dnfasok);
+        let best = (first..record_count)
+            .filter_map(|i| Some((i + 1, self.stats(i, s_type, dnfasok)?)))
+            .min_by_key(|(_, t)| *t);
+        let worst = (first..record_count)
+            .map(|i| (i + 1, self.stats(i, s_type, dnfasok)))
+            .min_by_key(|(_, t)| Reverse(t.unwrap_or(Milliseconds::MAX)))?;
+
+        Some((current, best, worst))
+    }
+
+    /// The best stats of the specified type, `None`
+    /// if there are not enough solves or every stats is DNF.
+    pub fn best_stats(&self, s_type: &StatsType, dnfasok: bool) -> Option<Milliseconds> {
+        self.rolling_stats(s_type, dnfasok)
+            .iter()
+            .flatten()
+            .min()
+            .copied()
+    }
+
+    /// The stats of the specified type over the last solves,
+    /// `None` if there are not enough solves or it's DNF.
+    pub fn current_stats(&self, s_type: &StatsType, dnfasok: bool) -> Option<Milliseconds> {
+        self.rolling_stats(s_type, dnfasok)
+            .last()
+            .copied()
+            .flatten()
+    }
+
+    /// The count of stats of the specified type under
+    /// the threshold, and the count of all stats, DNFs included.
+    pub fn subx_count(
+        &self,
+        s_type: &StatsType,
+        dnfasok: bool,
+        threshold: Milliseconds,
+    ) -> (usize, usize) {
+        let under = self
+            .stats_data(s_type, dnfasok)
+            .into_iter()
+            .filter(|t| *t < threshold)
+            .count();
+        let total = self.record_count().saturating_sub(s_type.scale() - 1);
+
+        (under, total)
+    }
+
+    /// A trend of the rate (in 0.001%) of stats of the specified
+    /// type under the threshold, over every window of stats.
+    pub fn subx_trend(
+        &self,
+        s_type: &StatsType,
+        dnfasok: bool,
+        threshold: Milliseconds,
+        window: usize,
+    ) -> Vec<TrendPoint> {
+        let data = self.rolling_stats(s_type, dnfasok);
+        let first = s_type.scale() - 1;
+        let is_under = |i: usize| data[i].is_some_and(|t| t < threshold);
+        let mut under = 0;
+
+        (0..self.record_count())
+            .map(|i| {
+                if i < first {
+                    return (i + 1, None);
+                }
+
+                under += usize::from(is_under(i));
+                if i >= first + window {
+                    under -= usize::from(is_under(i - window));
+                }
+
+                let rate = (i + 1 >= first + window).then(|| (under * 100_000 / window) as u32);
+                (i + 1, rate)
+            })
+            .collect()
+    }
+
+    /// A trend of the standard deviation of solve times
+    /// over windows of the scale of the specified type.
+    pub fn consistency(&self, s_type: &StatsType, dnfasok: bool) -> Vec<TrendPoint> {
+        rolling_sd(self.records(), s_type.scale(), dnfasok, self.rounding())
+            .into_iter()
+            .enumerate()
+            .map(|(i, sd)| (i + 1, sd))
+            .collect()
+    }
+
+    /// Draws a chart on the drawing area of any backend.
+    pub fn draw_chart<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        chart: &Chart,
+        desc: &str,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let desc = desc.trim();
+
+        match chart {
+            Chart::Grouping {
+                groups,
+                interval,
+                markers,
+                overlay,
+            } => self.draw_grouping(root, groups, *interval, markers, *overlay, desc),
+            Chart::Trending {
+                trends,
+                x_desc,
+                y_scale,
+            } => self.draw_trending(root, trends, x_desc, desc, *y_scale),
+            Chart::Heatmap {
+                rows,
+                x_labels,
+                y_labels,
+                formatter,
+            } => self.draw_heatmap(root, rows, x_labels, y_labels, desc, formatter),
+        }
+    }
+
+    /// Draws an image on the drawing area, visualizes grouping
+    /// results, with labeled vertical lines at the markers, and
+    /// translucent histograms with a legend if overlaid.
+    pub fn draw_grouping<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
         groups: &[GroupTime],
         interval: Milliseconds,
+        markers: &[(String, Milliseconds)],
+        overlay: Option<Overlay>,
         desc: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let count_max = groups.iter().map(|g| g.1).max().unwrap_or_default();
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let all_groups = || groups.iter().chain(overlay.iter().flat_map(|o| o.0.iter()));
+        let count_max = all_groups().map(|g| g.1).max().unwrap_or_default();
 
-        let (t_min, t_max) = (groups[0].0, groups[groups.len() - 1].0);
+        let t_min = all_groups().map(|g| g.0).min().unwrap_or_default();
+        let t_max = all_groups().map(|g| g.0).max().unwrap_or_default();
 
-        let root = CanvasBackend::with_canvas_object(canvas.clone())
-            .ok_or("Failed to acquire canvas backend")?
-            .into_drawing_area();
         root.fill(&WHITE)?;
 
         let x_margin = (interval.as_seconds() * SPACING_RATE * 100.0).min(2.0);
         let y_margin = (count_max as f32 * SPACING_RATE).max(1.0) as u32;
         let x_spec = (t_min.as_seconds().max(x_margin) - x_margin)..(t_max.as_seconds() + x_margin);
         let y_spec = 0u32..count_max as u32 + y_margin;
-        let mut chart = ChartBuilder::on(&root)
+        let mut chart = ChartBuilder::on(root)
             .caption(desc, (MONOSPACE, CAPTION_FONT_SIZE).into_font())
             .margin(MARGIN)
             .x_label_area_size(LABEL_AREA_SIZE)
@@ -305,41 +771,99 @@ impl Session {
             .axis_desc_style((MONOSPACE, AXIS_DESC_FONT_SIZE).into_font())
             .x_desc(t!("chart.group-x-desc"))
             .y_desc(t!("chart.group-y-desc"))
-            .x_label_formatter(&Seconds::to_readable_string)
+            .x_label_formatter(&|v| self.puzzle().format_seconds(v))
             .draw()?;
 
-        let coords: Vec<(f32, u32)> = groups
-            .iter()
-            .map(|g| (g.0.as_seconds(), g.1 as u32))
-            .collect();
+        let bars = |groups: &[GroupTime], color: RGBAColor| {
+            groups
+                .iter()
+                .map(|g| {
+                    let x0 = g.0.as_seconds();
+                    let x1 = x0 + interval.as_seconds();
+
+                    Rectangle::new([(x0, 0), (x1, g.1 as u32)], color.filled())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        match overlay {
+            None => {
+                chart.draw_series(bars(groups, PLOT_COLOR.to_rgba()))?;
+            }
+            Some((overlay, label, overlay_label)) => {
+                let (color, overlay_color) = (PLOT_COLOR.mix(0.6), OVERLAY_COLOR.mix(0.6));
+
+                chart
+                    .draw_series(bars(groups, color))?
+                    .label(label)
+                    .legend(move |(x, y)| {
+                        Rectangle::new([(x, y - 10), (x + 20, y + 10)], color.filled())
+                    });
+                chart
+                    .draw_series(bars(overlay, overlay_color))?
+                    .label(overlay_label)
+                    .legend(move |(x, y)| {
+                        Rectangle::new([(x, y - 10), (x + 20, y + 10)], overlay_color.filled())
+                    });
+                chart
+                    .configure_series_labels()
+                    .label_font((MONOSPACE, LABEL_FONT_SIZE).into_font())
+                    .background_style(WHITE.mix(0.8))
+                    .border_style(BLACK)
+                    .draw()?;
+            }
+        }
 
-        chart.draw_series(coords.iter().map(|(x, y)| {
-            let x0 = *x;
-            let x1 = x0 + interval.as_seconds();
-            let y0 = 0;
-            let y1 = *y;
+        let y_top = count_max as u32 + y_margin;
+        for (label, time) in markers {
+            let x = time.as_seconds();
 
-            Rectangle::new([(x0, y0), (x1, y1)], PLOT_COLOR.filled())
-        }))?;
+            chart.draw_series(LineSeries::new(
+                [(x, 0), (x, y_top)],
+                MARKER_COLOR.stroke_width(STROKE_WIDTH),
+            ))?;
+            chart.draw_series(std::iter::once(Text::new(
+                label.clone(),
+                (x, y_top),
+                (MONOSPACE, LABEL_FONT_SIZE)
+                    .into_font()
+                    .color(&MARKER_COLOR),
+            )))?;
+        }
 
         root.present()?;
 
         Ok(())
     }
 
-    /// Draws an image on canvas, visualizes trending results.
-    pub fn draw_trending(
+    /// Draws an image on the drawing area, visualizes trending
+    /// results, the values are labeled by the specified formatter.
+    pub fn draw_trending<DB: DrawingBackend>(
         &self,
-        canvas: &HtmlCanvasElement,
-        trends: &[(usize, u32)],
+        root: &DrawingArea<DB, Shift>,
+        trends: &[TrendPoint],
+        x_desc: &str,
         desc: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let real_point_segments = |times: &[(usize, u32)]| -> Vec<(usize, usize)> {
+        y_scale: TrendScale,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let value = |v: Milliseconds| match y_scale {
+            TrendScale::Time(_) => v.as_seconds(),
+            TrendScale::Count => v as f32,
+        };
+        let y_formatter = |v: &f32| match y_scale {
+            TrendScale::Time(formatter) => formatter(v),
+            TrendScale::Count => format!("{:.0}", v),
+        };
+
+        let real_point_segments = |times: &[TrendPoint]| -> Vec<(usize, usize)> {
             let mut segments = Vec::new();
             let mut start = None;
 
             for (i, t) in times.iter().enumerate() {
-                if t.1 > 0 {
+                if t.1.is_some() {
                     if start.is_none() {
                         start = Some(i);
                     }
@@ -355,25 +879,29 @@ impl Session {
             segments
         };
 
-        let n = self.record_count();
+        let n = trends.len();
         let min = trends
             .iter()
-            .map(|data| data.1)
-            .filter(|t| *t > 0)
+            .filter_map(|data| data.1)
             .min()
             .unwrap_or_default();
-        let max = trends.iter().map(|data| data.1).max().unwrap_or_default();
-        let (t_min, t_max) = (min.as_seconds(), max.as_seconds());
+        let max = trends
+            .iter()
+            .filter_map(|data| data.1)
+            .max()
+            .unwrap_or_default();
+        let (t_min, t_max) = (value(min), value(max));
 
-        let root = CanvasBackend::with_canvas_object(canvas.clone())
-            .ok_or("Failed to acquire canvas backend")?
-            .into_drawing_area();
         root.fill(&WHITE)?;
 
-        let margin = (t_max - t_min) * SPACING_RATE;
+        let margin = if t_max > t_min {
+            (t_max - t_min) * SPACING_RATE
+        } else {
+            t_max.max(1.0) * SPACING_RATE
+        };
         let x_spec = 1..n + 1;
         let y_spec = (t_min - margin).max(0.0)..t_max + margin;
-        let mut chart = ChartBuilder::on(&root)
+        let mut chart = ChartBuilder::on(root)
             .caption(desc, (MONOSPACE, CAPTION_FONT_SIZE).into_font())
             .margin(MARGIN)
             .x_label_area_size(LABEL_AREA_SIZE)
@@ -384,13 +912,13 @@ impl Session {
             .configure_mesh()
             .label_style((MONOSPACE, LABEL_FONT_SIZE).into_font())
             .axis_desc_style((MONOSPACE, AXIS_DESC_FONT_SIZE).into_font())
-            .x_desc(t!("chart.trend-x-desc"))
-            .y_label_formatter(&Seconds::to_readable_string)
+            .x_desc(x_desc)
+            .y_label_formatter(&y_formatter)
             .draw()?;
 
         for (start, end) in real_point_segments(trends) {
             chart.draw_series(LineSeries::new(
-                (start..end).map(|i| (trends[i].0, trends[i].1.as_seconds())),
+                (start..end).map(|i| (trends[i].0, value(trends[i].1.unwrap_or_default()))),
                 PLOT_COLOR.stroke_width(STROKE_WIDTH),
             ))?;
         }
@@ -400,6 +928,128 @@ impl Session {
         Ok(())
     }
 
+    /// Draws an image on the drawing area, visualizes a grid of
+    /// cells shaded by their values, labeled by the formatter.
+    pub fn draw_heatmap<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        rows: &[HeatmapRow],
+        x_labels: &[String],
+        y_labels: &[String],
+        desc: &str,
+        formatter: &dyn Fn(u32) -> String,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let values = || rows.iter().flatten().flatten();
+        let min = values().min().copied().unwrap_or_default();
+        let max = values().max().copied().unwrap_or_default();
+        let (column_count, row_count) = (x_labels.len() as i32, rows.len() as i32);
+
+        root.fill(&WHITE)?;
+
+        let mut chart = ChartBuilder::on(root)
+            .caption(desc, (MONOSPACE, CAPTION_FONT_SIZE).into_font())
+            .margin(MARGIN)
+            .x_label_area_size(LABEL_AREA_SIZE / 2)
+            .y_label_area_size(LABEL_AREA_SIZE)
+            .build_cartesian_2d(
+                (0..column_count - 1).into_segmented(),
+                (0..row_count - 1).into_segmented(),
+            )?;
+
+        let label = |labels: &[String], v: &SegmentValue<i32>| match v {
+            SegmentValue::CenterOf(i) => labels.get(*i as usize).cloned().unwrap_or_default(),
+            _ => String::new(),
+        };
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .label_style((MONOSPACE, LABEL_FONT_SIZE).into_font())
+            .x_labels(x_labels.len())
+            .y_labels(rows.len())
+            .x_label_formatter(&|v| label(x_labels, v))
+            .y_label_formatter(&|v| match v {
+                SegmentValue::CenterOf(i) => {
+                    label(y_labels, &SegmentValue::CenterOf(row_count - 1 - i))
+                }
+                _ => String::new(),
+            })
+            .draw()?;
+
+        for (i, row) in rows.iter().enumerate() {
+            let y = row_count - 1 - i as i32;
+
+            for (x, value) in row.iter().enumerate() {
+                let x = x as i32;
+                let ratio = match value {
+                    Some(v) if max > min => (v - min) as f64 / (max - min) as f64,
+                    _ => 0.0,
+                };
+                let channel = |low: u8, high: u8| {
+                    (low as f64 + (high as f64 - low as f64) * ratio).round() as u8
+                };
+                let color = if value.is_some() {
+                    RGBColor(
+                        channel(HEAT_LOW_COLOR.0, HEAT_HIGH_COLOR.0),
+                        channel(HEAT_LOW_COLOR.1, HEAT_HIGH_COLOR.1),
+                        channel(HEAT_LOW_COLOR.2, HEAT_HIGH_COLOR.2),
+                    )
+                } else {
+                    HEAT_EMPTY_COLOR
+                };
+
+                chart.draw_series(std::iter::once(Rectangle::new(
+                    [
+                        (SegmentValue::Exact(x), SegmentValue::Exact(y)),
+                        (SegmentValue::Exact(x + 1), SegmentValue::Exact(y + 1)),
+                    ],
+                    color.filled(),
+                )))?;
+
+                if let Some(v) = value {
+                    let text_color = if ratio > 0.5 { &WHITE } else { &BLACK };
+                    chart.draw_series(std::iter::once(Text::new(
+                        formatter(*v),
+                        (SegmentValue::CenterOf(x), SegmentValue::CenterOf(y)),
+                        (MONOSPACE, LABEL_FONT_SIZE * 2 / 3)
+                            .into_font()
+                            .color(text_color)
+                            .pos(Pos::new(HPos::Center, VPos::Center)),
+                    )))?;
+                }
+            }
+        }
+
+        root.present()?;
+
+        Ok(())
+    }
+
+    /// The mean and fewest move counts, and the mean and best
+    /// turns per second over non-DNF `Record`s with a smart
+    /// cube reconstruction, `None` if there is no such `Record`.
+    pub fn reconstruction_summary(&self) -> Option<(f32, usize, f32, f32)> {
+        let data: Vec<(usize, f32)> = self
+            .records_not_dnf()
+            .iter()
+            .filter_map(|r| Some((r.reconstruction()?.move_count(), r.tps()?)))
+            .collect();
+
+        if data.is_empty() {
+            return None;
+        }
+
+        let count = data.len() as f32;
+        let moves_mean = data.iter().map(|d| d.0 as f32).sum::<f32>() / count;
+        let moves_best = data.iter().map(|d| d.0).min().unwrap_or_default();
+        let tps_mean = data.iter().map(|d| d.1).sum::<f32>() / count;
+        let tps_best = data.iter().map(|d| d.1).fold(0.0, f32::max);
+
+        Some((moves_mean, moves_best, tps_mean, tps_best))
+    }
+
     /// `Record`s with a comment.
     pub fn commented_records(&self) -> Vec<(usize, Rc<Record>)> {
         self.records()
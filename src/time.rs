@@ -4,6 +4,9 @@ pub type Milliseconds = u32;
 /// Seconds in f32.
 pub type Seconds = f32;
 
+/// Milliseconds in a centisecond.
+const CENTI: Milliseconds = 10;
+
 /// Milliseconds in a second.
 const SEC: Milliseconds = 1_000;
 
@@ -13,6 +16,63 @@ const MIN: Milliseconds = 60_000;
 /// Milliseconds in an hour.
 const HOUR: Milliseconds = 3_600_000;
 
+/// How solve times and stats are rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rounding {
+    /// Singles as recorded, stats rounded at 1 millis.
+    #[default]
+    Millis,
+
+    /// As WCA regulations require, singles are truncated and stats
+    /// are rounded (x.xx5 up) to centiseconds, values over 10 minutes
+    /// are rounded (x.5 up) to seconds.
+    Wca,
+}
+
+impl Rounding {
+    /// Rounds a single solve time.
+    pub const fn single(&self, time: Milliseconds) -> Milliseconds {
+        match self {
+            Self::Millis => time,
+            Self::Wca if time >= MIN * 10 => (time + SEC / 2) / SEC * SEC,
+            Self::Wca => time / CENTI * CENTI,
+        }
+    }
+
+    /// Rounds the arithmetic mean over a sum of `Milliseconds`,
+    /// in integer arithmetic, 0 if the count is 0.
+    pub fn mean(&self, sum: u64, count: usize) -> Milliseconds {
+        if count == 0 {
+            return 0;
+        }
+
+        let count = count as u64;
+        let round = |unit: u64| (sum * 2 + unit * count) / (unit * count * 2) * unit;
+
+        let mean = match self {
+            Self::Millis => round(1),
+            Self::Wca if sum >= (MIN * 10) as u64 * count => round(SEC as u64),
+            Self::Wca => round(CENTI as u64),
+        };
+
+        mean as Milliseconds
+    }
+
+    /// Formats a time by this mode, which shows centiseconds for
+    /// WCA, rounded (x.xx5 up) in case it's not a WCA result.
+    pub fn format(&self, time: Milliseconds) -> String {
+        match self {
+            Self::Millis => time.to_readable_string(),
+            Self::Wca => {
+                let centis = time.saturating_add(CENTI / 2) / CENTI * CENTI;
+                let mut readable = centis.to_readable_string();
+                readable.pop();
+                readable
+            }
+        }
+    }
+}
+
 /// Formats a time type into a human-readable string.
 pub trait HumanReadable {
     /// The human-readable string of the type.
@@ -54,6 +114,37 @@ impl HumanReadable for Seconds {
     }
 }
 
+/// Parses a human-readable time, e.g. `15`, `15.00`,
+/// `1:30.00` or `1:0:00.000`, back into `Milliseconds`.
+pub fn parse_readable(s: &str) -> Option<Milliseconds> {
+    let s = s.trim();
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+
+    if frac.len() > 3 || !frac.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let millis = format!("{:0<3}", frac).parse::<Milliseconds>().ok()?;
+
+    let parts: Vec<&str> = whole.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let mut secs: Milliseconds = 0;
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let value = part.parse::<Milliseconds>().ok()?;
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        secs = secs.checked_mul(60)?.checked_add(value)?;
+    }
+
+    secs.checked_mul(SEC)?.checked_add(millis)
+}
+
 /// Represents a `Milliseconds` value as `Seconds`.
 pub trait AsSeconds {
     /// The `Seconds` value of a `Milliseconds`.
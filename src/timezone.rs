@@ -0,0 +1,121 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, Local, Offset, TimeZone as _};
+use chrono_tz::{TZ_VARIANTS, Tz};
+
+/// Seconds in an hour.
+const HOUR: i32 = 3_600;
+
+/// A timezone to localize the date-times of `Record`s,
+/// either by the IANA rules or by a fixed offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeZone {
+    /// An IANA timezone, e.g. `Europe/Berlin`, with
+    /// DST changes applied per timestamp.
+    Iana(Tz),
+
+    /// A fixed offset from UTC, e.g. `+08:00`.
+    Fixed(FixedOffset),
+
+    /// The timezone of the system, when its IANA name is
+    /// unknown, with DST changes applied per timestamp.
+    Local,
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self::Iana(Tz::UTC)
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Iana(tz) => write!(f, "{}", tz.name()),
+            Self::Fixed(offset) => write!(f, "{}", offset),
+            Self::Local => write!(f, "Local"),
+        }
+    }
+}
+
+impl TryFrom<&str> for TimeZone {
+    type Error = ParseTimeZoneError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+
+        if let Some(sign) = value.chars().next().filter(|c| *c == '+' || *c == '-') {
+            let (hours, minutes) = value[1..].split_once(':').unwrap_or((&value[1..], "0"));
+            let digits = |s: &str| {
+                s.bytes()
+                    .all(|b| b.is_ascii_digit())
+                    .then(|| s.parse::<i32>().ok())
+                    .flatten()
+                    .ok_or(ParseTimeZoneError::InvalidOffset)
+            };
+            let (hours, minutes) = (digits(hours)?, digits(minutes)?);
+            if hours > 23 || minutes >= 60 {
+                return Err(ParseTimeZoneError::InvalidOffset);
+            }
+
+            let seconds = (hours * HOUR + minutes * 60) * if sign == '-' { -1 } else { 1 };
+            return FixedOffset::east_opt(seconds)
+                .map(Self::Fixed)
+                .ok_or(ParseTimeZoneError::InvalidOffset);
+        }
+
+        TZ_VARIANTS
+            .iter()
+            .find(|tz| tz.name().eq_ignore_ascii_case(value))
+            .map(|tz| Self::Iana(*tz))
+            .ok_or(ParseTimeZoneError::UnknownName)
+    }
+}
+
+impl TimeZone {
+    /// The offset from UTC in seconds at the
+    /// specified Unix timestamp in seconds.
+    pub fn offset_seconds(&self, timestamp: i64) -> i64 {
+        let Some(utc) = DateTime::from_timestamp(timestamp, 0) else {
+            return 0;
+        };
+
+        let offset = match self {
+            Self::Iana(tz) => tz.offset_from_utc_datetime(&utc.naive_utc()).fix(),
+            Self::Fixed(offset) => *offset,
+            Self::Local => Local.offset_from_utc_datetime(&utc.naive_utc()).fix(),
+        };
+
+        offset.local_minus_utc() as i64
+    }
+
+    /// Shifts a Unix timestamp in seconds to the local
+    /// date-time, which is then treated as UTC.
+    pub fn localize(&self, timestamp: i64) -> i64 {
+        timestamp + self.offset_seconds(timestamp)
+    }
+}
+
+/// An error which can be returned
+/// when parsing a `TimeZone`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseTimeZoneError {
+    /// Not an IANA timezone name.
+    UnknownName,
+
+    /// Not an offset like `+08:00`.
+    InvalidOffset,
+}
+
+impl fmt::Display for ParseTimeZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownName => write!(f, "unknown timezone name"),
+            Self::InvalidOffset => write!(f, "offset must be in range -23:59 to +23:59"),
+        }
+    }
+}
+
+impl Error for ParseTimeZoneError {}
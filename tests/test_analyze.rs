@@ -0,0 +1,376 @@
+use serde_json::json;
+
+use cstimer_analyzer_web::analyze::*;
+use cstimer_analyzer_web::chart::SvgCanvas;
+use cstimer_analyzer_web::options::{CalendarUnit, StatsType, WarmupScope};
+use cstimer_analyzer_web::parser::*;
+use cstimer_analyzer_web::session::Session;
+use cstimer_analyzer_web::timezone::TimeZone;
+
+fn sessions() -> Vec<Session> {
+    let records: Vec<_> = (0..20)
+        .map(|i| json!([[0, 10000 + i * 100], "R U", "", 1_700_000_000 + i * 60]))
+        .collect();
+    let session_data = json!({ "1": { "name": "main", "rank": 1 } });
+    let data = json!({
+        "session1": records,
+        "properties": { "sessionData": session_data.to_string() },
+    })
+    .to_string();
+
+    parse_sessions(&data, &TimeZone::default()).0
+}
+
+/// A session of the records.
+fn session(records: serde_json::Value) -> Session {
+    let data = json!({
+        "session1": records,
+        "properties": { "sessionData": json!({ "1": { "name": "main" } }).to_string() },
+    })
+    .to_string();
+
+    parse_sessions(&data, &TimeZone::default()).0.remove(0)
+}
+
+fn analyze(options: &str) -> String {
+    analyze_of(&sessions()[0], options, false)
+}
+
+fn analyze_of(session: &Session, options: &str, dnfasok: bool) -> String {
+    let options = parse_options(options);
+
+    let mut report = Vec::new();
+    analyze_session(session, &options, dnfasok, &mut report, &mut SvgCanvas).unwrap();
+
+    String::from_utf8(report).unwrap()
+}
+
+#[test]
+fn test_inline_svg() {
+    let report = analyze("trend(ao5)\ngroup(single, 0)");
+
+    assert_eq!(report.matches("<svg").count(), 2);
+    let chart = &report[report.find("<div class=\"chart\"").unwrap()..];
+    let chart = &chart[..chart.find("</div>").unwrap()];
+    assert!(!chart.contains("\n\n"));
+}
+
+#[test]
+fn test_svg_title_escaped() {
+    let data = json!({
+        "session1": [[[0, 10000], "R U", "", 1_700_000_000]],
+        "properties": {
+            "sessionData": json!({ "1": { "name": "a \"b\" <c> & d" } }).to_string(),
+        },
+    })
+    .to_string();
+    let sessions = parse_sessions(&data, &TimeZone::default()).0;
+    let report = analyze_of(&sessions[0], "group(single, 0)", false);
+
+    let title = report
+        .lines()
+        .find(|l| l.starts_with("<div class=\"chart\""))
+        .unwrap();
+    assert!(title.contains("a &quot;b&quot; &lt;c&gt; &amp; d"));
+}
+
+#[test]
+fn test_time_of_day() {
+    let report = analyze("timeofday");
+
+    assert_eq!(report.matches("<svg").count(), 2);
+    assert!(report.contains("`Tue 22:00 ~ 23:00`"));
+}
+
+#[test]
+fn test_streaks() {
+    let report = analyze("streaks");
+
+    assert!(report.contains("`1` day (2023-11-14 ~ 2023-11-14)"));
+    assert!(report.contains("`20` at most"));
+}
+
+#[test]
+fn test_compare() {
+    let report = analyze("compare(20; 5)");
+
+    assert_eq!(report.matches("<svg").count(), 1);
+    assert!(report.contains("Welch's t-test"));
+    assert!(report.contains("| mean | `10.950` | `11.700` | `+0.750` |"));
+}
+
+#[test]
+fn test_rolling() {
+    let report = analyze("rolling(ao5, ao100)");
+
+    assert!(
+        report.contains(
+            "| ao5 | `11.700` | `10.200` | 5 | 2023-11-14 | `11.700` | 20 | 2023-11-14 |"
+        )
+    );
+    assert!(report.contains("| ao100 | - | - | - | - | - | - | - |"));
+}
+
+#[test]
+fn test_pb_window_detail() {
+    let report = analyze("pbs(ao5)");
+
+    assert!(report.contains(
+        "[#1-#5] `10.200` @2023-11-14 22:17:20: `(10.000) 10.100 10.200 10.300 (10.400)`"
+    ));
+    assert!(report.contains("[#3] @2023-11-14 22:15:20"));
+}
+
+#[test]
+fn test_top_and_worst() {
+    let report = analyze("top(ao5, 2, distinct)");
+    assert!(report.contains("| 1 | `10.200` | #1-#5 |"));
+    assert!(report.contains("| 2 | `10.700` | #6-#10 |"));
+
+    let report = analyze("worst(single, 1)");
+    assert!(report.contains("| 1 | `11.900` | #20 |"));
+    assert!(report.contains("[#20] @"));
+}
+
+#[test]
+fn test_overview_table() {
+    let mut report = Vec::new();
+    write_analysis_info(&mut report, &sessions(), &[], &parse_options("summary")).unwrap();
+    let report = String::from_utf8(report).unwrap();
+
+    // times are 10.0, 10.1, ..., 11.9
+    assert!(report.contains(
+        "| [[#1] **main**](#session1) | 3x3x3 | `20` | `1` | `10.000` | `10.200` | `10.550` | - | `11.700` | `11.350` | 2023-11-14 |"
+    ));
+}
+
+#[test]
+fn test_dnf_single_under_dnfasok() {
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_700_000_000],
+        [[-1, 5000], "R U", "", 1_700_000_060],
+        [[0, 9000], "R U", "", 1_700_000_120],
+    ]));
+
+    let pbs = session.pbs(&StatsType::Single, true);
+    assert_eq!(
+        pbs.iter().map(|pb| (pb.0, pb.1)).collect::<Vec<_>>(),
+        [(0, 10000), (2, 9000)]
+    );
+}
+
+#[test]
+fn test_short_sittings() {
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_700_000_000],
+        [[0, 11000], "R U", "", 1_700_003_600],
+        [[0, 12000], "R U", "", 1_700_003_660],
+        [[0, 13000], "R U", "", 1_700_007_200],
+        [[0, 14000], "R U", "", 1_700_007_260],
+        [[0, 15000], "R U", "", 1_700_007_320],
+    ]));
+    let report = analyze_of(&session, "sittings(30)", false);
+
+    assert!(report.contains("| 1 | 2023-11-14 22:13 | `1` | `0 min` | `10.000` | - |"));
+    assert!(report.contains("| 2 | 2023-11-14 23:13 | `2` | `1 min` | `11.500` | - |"));
+    assert!(report.contains("| 3 | 2023-11-15 00:13 | `3` | `2 min` | `14.000` | `14.000` |"));
+    assert!(!report.contains("DNF"));
+}
+
+#[test]
+fn test_short_bld_summary() {
+    let data = json!({
+        "session1": [
+            [[0, 60000], "R U", "", 1_700_000_000],
+            [[0, 70000], "R U", "", 1_700_000_060],
+        ],
+        "properties": {
+            "sessionData": json!({ "1": { "name": "bld", "opt": { "scrType": "333bf" } } })
+                .to_string(),
+        },
+    })
+    .to_string();
+    let sessions = parse_sessions(&data, &TimeZone::default()).0;
+    let report = analyze_of(&sessions[0], "summary", false);
+
+    assert!(report.contains("| `1:00.000` | `1:10.000` | `1:05.000` | - |"));
+}
+
+#[test]
+fn test_all_dnf_calendar_bucket() {
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_700_000_000],
+        [[0, 12000], "R U", "", 1_700_000_060],
+        [[-1, 9000], "R U", "", 1_700_604_800],
+        [[-1, 11000], "R U", "", 1_700_604_860],
+    ]));
+    let report = analyze_of(&session, "calendar(single, week)", true);
+
+    assert!(report.contains("| 2023-11-13 | `2` | `10.000` | `11.000` | `11.000` |"));
+    assert!(report.contains("| 2023-11-20 | `2` | `DNF` | `10.000` | `DNF` |"));
+}
+
+#[test]
+fn test_percentiles() {
+    // nearest ranks 2, 10 and 18 of 10.0, 10.1, ..., 11.9
+    let report = analyze("percentiles(single)");
+    assert!(report.contains("| all records | `10.100` | `10.900` | `11.700` |"));
+
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_700_000_000],
+        [[-1, 8000], "R U", "", 1_700_000_060],
+        [[0, 12000], "R U", "", 1_700_000_120],
+        [[0, 11000], "R U", "", 1_700_000_180],
+        [[-1, 9000], "R U", "", 1_700_000_240],
+    ]));
+    let report = analyze_of(&session, "percentiles(single, 1, 60, 61, 99)", false);
+    assert!(report.contains("| all records | `10.000` | `12.000` | `DNF` | `DNF` |"));
+}
+
+#[test]
+fn test_subx() {
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_700_000_000],
+        [[-1, 8000], "R U", "", 1_700_000_060],
+        [[0, 12000], "R U", "", 1_700_000_120],
+        [[0, 11000], "R U", "", 1_700_000_180],
+        [[0, 9000], "R U", "", 1_700_000_240],
+    ]));
+
+    // a time on the threshold is not under it
+    assert_eq!(session.subx_count(&StatsType::Single, false, 11000), (2, 5));
+    assert_eq!(session.subx_count(&StatsType::Single, true, 11000), (2, 5));
+    assert_eq!(
+        session.subx_count(&StatsType::Mean(3), false, 11000),
+        (1, 3)
+    );
+    assert_eq!(session.subx_count(&StatsType::Mean(3), true, 11000), (3, 3));
+
+    assert_eq!(
+        session.subx_trend(&StatsType::Single, false, 11000, 2),
+        [
+            (1, None),
+            (2, Some(50_000)),
+            (3, Some(0)),
+            (4, Some(0)),
+            (5, Some(50_000))
+        ]
+    );
+
+    let report = analyze_of(&session, "subx(single, 11)", false);
+    assert!(report.contains("| all records | `2/5` | `40.00%` |"));
+}
+
+#[test]
+fn test_warmup() {
+    // sittings of 3 and 2 solves on a day, and a solve on the next day
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_700_000_000],
+        [[-1, 12000], "R U", "", 1_700_000_060],
+        [[0, 14000], "R U", "", 1_700_000_120],
+        [[0, 20000], "R U", "", 1_700_003_600],
+        [[0, 16000], "R U", "", 1_700_003_660],
+        [[0, 8000], "R U", "", 1_700_086_400],
+    ]));
+
+    assert_eq!(
+        session.warmup(&WarmupScope::Sitting(30), false),
+        [
+            (3, 0, Some(12667)),
+            (2, 1, Some(16000)),
+            (1, 0, Some(14000))
+        ]
+    );
+    assert_eq!(
+        session.warmup(&WarmupScope::Sitting(30), true)[1],
+        (2, 1, Some(14000))
+    );
+    assert_eq!(
+        session.warmup(&WarmupScope::Day, false),
+        [
+            (2, 0, Some(9000)),
+            (1, 1, None),
+            (1, 0, Some(14000)),
+            (1, 0, Some(20000)),
+            (1, 0, Some(16000))
+        ]
+    );
+}
+
+#[test]
+fn test_split_by_calendar() {
+    // 2024-03-31 (Sun) twice, 2024-04-01 (Mon), 2024-04-07 (Sun) and 2024-04-08 (Mon)
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_711_886_400],
+        [[0, 11000], "R U", "", 1_711_886_460],
+        [[0, 12000], "R U", "", 1_711_972_800],
+        [[-1, 14000], "R U", "", 1_712_491_200],
+        [[0, 9000], "R U", "", 1_712_577_600],
+    ]));
+    let buckets = |unit| -> Vec<(String, usize, Option<u32>)> {
+        session
+            .split_by_calendar(unit)
+            .into_iter()
+            .map(|(start, bucket)| {
+                (
+                    start.to_string(),
+                    bucket.record_count(),
+                    bucket.stats_mean(&StatsType::Single, false),
+                )
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        buckets(CalendarUnit::Week),
+        [
+            (String::from("2024-03-25"), 2, Some(10500)),
+            (String::from("2024-04-01"), 2, Some(12000)),
+            (String::from("2024-04-08"), 1, Some(9000)),
+        ]
+    );
+    assert_eq!(
+        buckets(CalendarUnit::Month),
+        [
+            (String::from("2024-03-01"), 2, Some(10500)),
+            (String::from("2024-04-01"), 3, Some(10500)),
+        ]
+    );
+}
+
+#[test]
+fn test_summary_dispersion() {
+    let session = session(json!([
+        [[0, 10000], "R U", "", 1_700_000_000],
+        [[0, 12000], "R U", "", 1_700_000_060],
+        [[0, 14000], "R U", "", 1_700_000_120],
+        [[0, 16000], "R U", "", 1_700_000_180],
+        [[-1, 20000], "R U", "", 1_700_000_240],
+    ]));
+
+    // 10, 12, 14 and 16 seconds
+    let report = analyze_of(&session, "summary", false);
+    assert!(report.contains("| `2.582` | `19.86%` | `3.000` | `2.000` |"));
+
+    // 10, 12, 14, 16 and 20 seconds
+    let report = analyze_of(&session, "summary", true);
+    assert!(report.contains("| `3.847` | `26.72%` | `4.000` | `2.000` |"));
+}
+
+#[test]
+fn test_merge_dated_by_records() {
+    let data = json!({
+        "session1": [[[0, 10000], "R U", "", 1_700_000_000]],
+        "session2": [[[0, 10000], "R U", "", 1_700_086_400]],
+        "properties": {
+            "sessionData": json!({ "1": { "name": "a" }, "2": { "name": "b" } }).to_string(),
+        },
+    })
+    .to_string();
+    let sessions = parse_sessions(&data, &TimeZone::default()).0;
+    let merged = Session::merge(&sessions, String::from("all")).unwrap();
+    let (start, end) = merged.date_time();
+
+    assert_eq!(start.timestamp(), 1_700_000_000);
+    assert_eq!(end.timestamp(), 1_700_086_400);
+}
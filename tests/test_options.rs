@@ -0,0 +1,299 @@
+use cstimer_analyzer_web::options::*;
+
+#[test]
+fn test_parse_phases() {
+    let pairs = [
+        (
+            "phases(single)",
+            Some(AnalysisOption::Phases(StatsType::Single)),
+        ),
+        (
+            "phases(ao5)",
+            Some(AnalysisOption::Phases(StatsType::Average(5))),
+        ),
+        (
+            "phases(mo3)",
+            Some(AnalysisOption::Phases(StatsType::Mean(3))),
+        ),
+        ("phases(ao0)", None),
+        ("phases()", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_moves() {
+    let pairs = [
+        (
+            "moves(single)",
+            Some(AnalysisOption::Moves(StatsType::Single)),
+        ),
+        (
+            "moves(ao12)",
+            Some(AnalysisOption::Moves(StatsType::Average(12))),
+        ),
+        ("moves(x)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_timezone() {
+    let valid = [
+        "timezone(europe/berlin)",
+        "timezone(+08:00)",
+        "timezone(-3:30)",
+        "timezone(utc)",
+    ];
+    let invalid = [
+        "timezone(mars/olympus)",
+        "timezone(+08:60)",
+        "timezone(+24:00)",
+        "timezone(+1000000)",
+        "timezone(+99999999999)",
+        "timezone(+08:-30)",
+        "timezone(+-3)",
+        "timezone(+)",
+    ];
+
+    for input in valid {
+        assert!(AnalysisOption::try_from(input).is_ok());
+    }
+    for input in invalid {
+        assert!(AnalysisOption::try_from(input).is_err());
+    }
+}
+
+#[test]
+fn test_parse_stats_types() {
+    let pairs = [
+        ("md5", Some(StatsType::Median(5))),
+        ("md1", Some(StatsType::Single)),
+        ("bo3", Some(StatsType::BestOf(3))),
+        ("ao12t2", Some(StatsType::Trimmed(12, 2))),
+        ("ao5t0", Some(StatsType::Mean(5))),
+        ("ao4t2", None),
+        ("ao0t0", None),
+        ("ao5t", None),
+        ("bo0", None),
+    ];
+
+    for (input, s_type) in pairs {
+        assert_eq!(StatsType::try_from(input).ok(), s_type);
+    }
+    assert_eq!(StatsType::Trimmed(12, 2).to_string(), "ao12t2");
+}
+
+#[test]
+fn test_parse_consistency() {
+    assert_eq!(
+        AnalysisOption::try_from("consistency(ao12)").ok(),
+        Some(AnalysisOption::Consistency(StatsType::Average(12)))
+    );
+    assert_eq!(AnalysisOption::try_from("consistency(single)").ok(), None);
+}
+
+#[test]
+fn test_parse_percentiles() {
+    let pairs = [
+        (
+            "percentiles(ao5, 90, 10, 50)",
+            Some(AnalysisOption::Percentiles(
+                StatsType::Average(5),
+                vec![10, 50, 90],
+            )),
+        ),
+        (
+            "percentiles(single)",
+            Some(AnalysisOption::Percentiles(
+                StatsType::Single,
+                vec![10, 50, 90],
+            )),
+        ),
+        ("percentiles(single, 100)", None),
+        ("percentiles(single, x)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_subx() {
+    let pairs = [
+        (
+            "subx(single, 15.00)",
+            Some(AnalysisOption::SubX(StatsType::Single, 15_000)),
+        ),
+        (
+            "subx(ao5, 1:30)",
+            Some(AnalysisOption::SubX(StatsType::Average(5), 90_000)),
+        ),
+        ("subx(ao5, 0)", None),
+        ("subx(ao5)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_calendar() {
+    let pairs = [
+        (
+            "calendar(ao5, week)",
+            Some(AnalysisOption::Calendar(
+                StatsType::Average(5),
+                CalendarUnit::Week,
+            )),
+        ),
+        (
+            "calendar(single,month)",
+            Some(AnalysisOption::Calendar(
+                StatsType::Single,
+                CalendarUnit::Month,
+            )),
+        ),
+        ("calendar(single, year)", None),
+        ("calendar(single)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+
+    let date = |s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    assert_eq!(
+        CalendarUnit::Week.start_of(date("2024-03-31")),
+        date("2024-03-25")
+    );
+    assert_eq!(
+        CalendarUnit::Month.start_of(date("2024-03-31")),
+        date("2024-03-01")
+    );
+}
+
+#[test]
+fn test_parse_sittings() {
+    let pairs = [
+        ("sittings(30)", Some(AnalysisOption::Sittings(30))),
+        ("sittings( 5 )", Some(AnalysisOption::Sittings(5))),
+        ("sittings(0)", None),
+        ("sittings(-1)", None),
+        ("sittings()", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_warmup() {
+    let pairs = [
+        (
+            "warmup",
+            Some(AnalysisOption::Warmup(WarmupScope::Sitting(
+                DEFAULT_SITTING_GAP,
+            ))),
+        ),
+        (
+            "warmup(45)",
+            Some(AnalysisOption::Warmup(WarmupScope::Sitting(45))),
+        ),
+        (
+            "warmup(day)",
+            Some(AnalysisOption::Warmup(WarmupScope::Day)),
+        ),
+        ("warmup(0)", None),
+        ("warmup(week)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_compare() {
+    let date = |s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    let pairs = [
+        (
+            "compare(2025-01-01, 2025-03-31; 2025-04-01)",
+            Some(AnalysisOption::Compare(
+                TargetRange::DateRange(date("2025-01-01"), Some(date("2025-03-31"))),
+                TargetRange::DateRange(date("2025-04-01"), None),
+            )),
+        ),
+        (
+            "compare(200;50)",
+            Some(AnalysisOption::Compare(
+                TargetRange::SolvesCount(200),
+                TargetRange::SolvesCount(50),
+            )),
+        ),
+        ("compare(200, 50)", None),
+        ("compare(200; 0)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_top_and_worst() {
+    let pairs = [
+        (
+            "top(single, 10)",
+            Some(AnalysisOption::Top(StatsType::Single, 10, false)),
+        ),
+        (
+            "worst(ao5, 3, distinct)",
+            Some(AnalysisOption::Worst(StatsType::Average(5), 3, true)),
+        ),
+        ("top(ao5, 0)", None),
+        ("top(ao5)", None),
+        ("worst(ao5, 3, all)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
+
+#[test]
+fn test_parse_rolling() {
+    let pairs = [
+        (
+            "rolling",
+            Some(AnalysisOption::Rolling(vec![
+                StatsType::Average(5),
+                StatsType::Average(12),
+                StatsType::Average(100),
+            ])),
+        ),
+        (
+            "rolling(single, mo3, ao5, mo3)",
+            Some(AnalysisOption::Rolling(vec![
+                StatsType::Single,
+                StatsType::Mean(3),
+                StatsType::Average(5),
+            ])),
+        ),
+        ("rolling()", None),
+        ("rolling(ao5, xx)", None),
+    ];
+
+    for (input, option) in pairs {
+        assert_eq!(AnalysisOption::try_from(input).ok(), option);
+    }
+}
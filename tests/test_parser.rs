@@ -0,0 +1,172 @@
+use chrono::NaiveDate;
+use serde_json::json;
+
+use cstimer_analyzer_web::parser::*;
+use cstimer_analyzer_web::puzzle::PuzzleType;
+use cstimer_analyzer_web::record::SolveState;
+use cstimer_analyzer_web::timezone::TimeZone;
+
+/// 2024-03-30 22:30:00 UTC, before DST starts in Europe.
+const BEFORE_DST: i64 = 1_711_837_800;
+
+/// 2024-03-31 22:30:00 UTC, after DST starts in Europe.
+const AFTER_DST: i64 = 1_711_924_200;
+
+fn export() -> String {
+    let session_data = json!({
+        "1": { "name": "main", "rank": 1, "opt": {}, "date": [BEFORE_DST, AFTER_DST] },
+        "2": { "name": "oh", "rank": 2, "opt": { "scrType": "333oh" }, "date": [BEFORE_DST, AFTER_DST] },
+    });
+
+    json!({
+        "session1": [
+            [[0, 12000], "R U", "", BEFORE_DST],
+            [[2000, 10000], "R U", "pll skip", AFTER_DST],
+            [[-1, 15000, 9000, 4000], "R U", "", AFTER_DST],
+        ],
+        "session2": [
+            [[0, 20000], "R U", "", BEFORE_DST],
+            [[4000, 20000], "R U", "", BEFORE_DST],
+            [[0], "R U", "", BEFORE_DST],
+        ],
+        "session3": [
+            [[0, 30000], "R U", "", BEFORE_DST],
+            [[0, 31000], "R U", "", AFTER_DST],
+        ],
+        "properties": { "sessionData": session_data.to_string() },
+    })
+    .to_string()
+}
+
+#[test]
+fn test_parse_sessions() {
+    let (sessions, _) = parse_sessions(&export(), &TimeZone::default());
+
+    assert_eq!(sessions.len(), 3);
+    assert_eq!(sessions[0].name(), "main");
+    assert_eq!(sessions[0].record_count(), 3);
+    assert_eq!(*sessions[1].puzzle(), PuzzleType::OneHanded);
+
+    let records = sessions[0].records();
+    assert_eq!(records[1].solve_state(), SolveState::Plus2);
+    assert_eq!(records[1].time(), 12000);
+    assert_eq!(records[1].comment(), "pll skip");
+    assert_eq!(records[2].solve_state(), SolveState::Dnf);
+    assert_eq!(records[2].phases(), &[4000, 5000, 6000]);
+}
+
+#[test]
+fn test_parse_diagnostics() {
+    let (_, diagnostics) = parse_sessions(&export(), &TimeZone::default());
+
+    assert!(diagnostics.contains(&ParseDiagnostic::SkippedRecords {
+        session: 2,
+        reason: SkipReason::UnknownPenalty(4000),
+        count: 1,
+        indices: vec![2],
+    }));
+    assert!(diagnostics.contains(&ParseDiagnostic::SkippedRecords {
+        session: 2,
+        reason: SkipReason::InvalidField("time"),
+        count: 1,
+        indices: vec![3],
+    }));
+    assert!(diagnostics.contains(&ParseDiagnostic::MissingMetadata(3)));
+
+    let (sessions, diagnostics) = parse_sessions("{\"session1\": [", &TimeZone::default());
+    assert!(sessions.is_empty());
+    assert!(matches!(
+        diagnostics[..],
+        [ParseDiagnostic::InvalidJson { line: 1, .. }]
+    ));
+}
+
+#[test]
+fn test_parse_missing_metadata() {
+    let (sessions, _) = parse_sessions(&export(), &TimeZone::default());
+
+    let session = &sessions[2];
+    assert_eq!(session.rank(), 3);
+    assert_eq!(session.name(), "3");
+    assert_eq!(session.date_time().0.timestamp(), BEFORE_DST);
+    assert_eq!(session.date_time().1.timestamp(), AFTER_DST);
+}
+
+#[test]
+fn test_parse_timezone() {
+    let date = |timezone: &str| {
+        let timezone = TimeZone::try_from(timezone).unwrap();
+        let (sessions, _) = parse_sessions(&export(), &timezone);
+        sessions[0].records()[1].date_time().date_naive()
+    };
+
+    assert_eq!(date("UTC"), NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+    assert_eq!(
+        date("+01:00"),
+        NaiveDate::from_ymd_opt(2024, 3, 31).unwrap()
+    );
+    assert_eq!(
+        date("europe/berlin"),
+        NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()
+    );
+}
+
+#[test]
+fn test_grouped_skipped_records() {
+    let mut records = vec![json!([[0, 10000], "R U", "", BEFORE_DST])];
+    records.extend((0..5).map(|_| json!([[0], "R U", "", BEFORE_DST])));
+    let input = json!({
+        "properties": { "sessionData": json!({ "1": { "name": "main" } }).to_string() },
+        "session1": records,
+    })
+    .to_string();
+    let (_, diagnostics) = parse_sessions(&input, &TimeZone::default());
+
+    assert_eq!(
+        diagnostics,
+        vec![ParseDiagnostic::SkippedRecords {
+            session: 1,
+            reason: SkipReason::InvalidField("time"),
+            count: 5,
+            indices: vec![2, 3, 4],
+        }]
+    );
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "Session `1`, `5` records skipped (`#2`, `#3`, `#4`, ...): invalid time."
+    );
+}
+
+#[test]
+fn test_parse_streaming() {
+    let input = json!({
+        "properties": { "sessionData": "{}" },
+        "session1": [[[0, 12000], "R U", "", BEFORE_DST]],
+        "session2": "not records",
+        "session3": { "nested": [[0, 1]] },
+    })
+    .to_string();
+    let (sessions, diagnostics) = parse_sessions(&input, &TimeZone::default());
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].record_count(), 1);
+    assert_eq!(
+        diagnostics,
+        vec![
+            ParseDiagnostic::InvalidSession(2),
+            ParseDiagnostic::InvalidSession(3),
+            ParseDiagnostic::MissingMetadata(1),
+        ]
+    );
+
+    let (sessions, diagnostics) = parse_sessions("[1, 2]", &TimeZone::default());
+    assert!(sessions.is_empty());
+    assert_eq!(diagnostics, vec![ParseDiagnostic::NotAnObject]);
+
+    for input in ["", "garbage", "[1, 2"] {
+        let (_, diagnostics) = parse_sessions(input, &TimeZone::default());
+        assert!(matches!(
+            diagnostics[..],
+            [ParseDiagnostic::InvalidJson { line: 1, .. }]
+        ));
+    }
+}
@@ -0,0 +1,29 @@
+use cstimer_analyzer_web::puzzle::*;
+
+#[test]
+fn test_from_scr_type() {
+    let pairs = [
+        ("", PuzzleType::Cube(3)),
+        ("333", PuzzleType::Cube(3)),
+        ("222so", PuzzleType::Cube(2)),
+        ("444wca", PuzzleType::Cube(4)),
+        ("333oh", PuzzleType::OneHanded),
+        ("333bf", PuzzleType::Blindfolded(3)),
+        ("333ni", PuzzleType::Blindfolded(3)),
+        ("r3ni", PuzzleType::Other(String::from("r3ni"))),
+        ("333fm", PuzzleType::FewestMoves),
+        ("skbso", PuzzleType::Skewb),
+        ("udf", PuzzleType::Other(String::from("udf"))),
+    ];
+
+    for (scr_type, puzzle) in pairs {
+        assert_eq!(PuzzleType::from(scr_type), puzzle);
+    }
+}
+
+#[test]
+fn test_format_time() {
+    assert_eq!(PuzzleType::FewestMoves.format_time(28_000), "28");
+    assert_eq!(PuzzleType::FewestMoves.format_time(28_333), "28.33");
+    assert_eq!(PuzzleType::Cube(3).format_time(28_000), "28.000");
+}
@@ -0,0 +1,73 @@
+use cstimer_analyzer_web::puzzle::PuzzleType;
+use cstimer_analyzer_web::record::*;
+use cstimer_analyzer_web::session::Session;
+use cstimer_analyzer_web::time::Rounding;
+
+fn record_with_moves(time: u32, moves: &[(&str, u32)]) -> Record {
+    let moves = moves.iter().map(|(m, t)| ((*m).to_owned(), *t)).collect();
+
+    Record::from(SolveState::Ok, time, String::new(), String::new(), 0)
+        .with_reconstruction(Some(Reconstruction::from(moves)))
+}
+
+#[test]
+fn test_tps() {
+    let record = record_with_moves(2000, &[("R", 0), ("U", 500), ("R'", 1000), ("U'", 2000)]);
+    assert_eq!(
+        record.reconstruction().map(Reconstruction::move_count),
+        Some(4)
+    );
+    assert_eq!(record.tps(), Some(2.0));
+
+    let untimed = record_with_moves(1000, &[("R", 0), ("U", 0)]);
+    assert_eq!(untimed.tps(), Some(2.0));
+
+    let plain = Record::from(SolveState::Ok, 1000, String::new(), String::new(), 0);
+    assert_eq!(plain.tps(), None);
+}
+
+#[test]
+fn test_record_detail() {
+    let record = Record::from(
+        SolveState::Ok,
+        28_000,
+        String::from("R U"),
+        String::new(),
+        0,
+    );
+    let session = Session::from(
+        1,
+        String::from("fmc"),
+        PuzzleType::FewestMoves,
+        (0, 0),
+        vec![record.clone()],
+    );
+
+    assert!(record.to_string().contains("`28.000`"));
+    assert!(
+        session
+            .record_detail(&session.records()[0])
+            .to_string()
+            .contains("`28`\n")
+    );
+}
+
+#[test]
+fn test_record_detail_wca() {
+    let record = Record::from(SolveState::Ok, 12_345, String::new(), String::new(), 0);
+    let session = Session::from(
+        1,
+        String::from("3x3"),
+        PuzzleType::default(),
+        (0, 0),
+        vec![record],
+    )
+    .with_rounding(Rounding::Wca);
+
+    assert!(
+        session
+            .record_detail(&session.records()[0])
+            .to_string()
+            .contains("`12.34`")
+    );
+}
@@ -0,0 +1,158 @@
+use std::rc::Rc;
+
+use cstimer_analyzer_web::options::StatsType;
+use cstimer_analyzer_web::record::*;
+use cstimer_analyzer_web::rolling::*;
+use cstimer_analyzer_web::time::Rounding;
+
+/// Records with pseudo-random times, ties and DNFs.
+fn records(n: usize) -> Vec<Rc<Record>> {
+    let mut seed: u32 = 42;
+
+    (0..n)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let time = 8_000 + (seed >> 8) % 40 * 100;
+            let solve_state = if (seed >> 4).is_multiple_of(13) {
+                SolveState::Dnf
+            } else {
+                SolveState::Ok
+            };
+
+            Rc::new(Record::from(
+                solve_state,
+                time,
+                String::new(),
+                String::new(),
+                0,
+            ))
+        })
+        .collect()
+}
+
+/// The average by sorting every window.
+fn naive_average(chunk: &[Rc<Record>], dnfasok: bool) -> Option<u32> {
+    let cut_off = (chunk.len() as f32 * CUT_OFF).ceil() as usize;
+    let take = chunk.len().saturating_sub(cut_off * 2);
+    let is_dnf = |r: &Record| r.solve_state().is_dnf() && !dnfasok;
+
+    if take == 0 || chunk.iter().filter(|r| is_dnf(r)).count() > cut_off {
+        return None;
+    }
+
+    let mut times: Vec<u32> = chunk
+        .iter()
+        .map(|r| if is_dnf(r) { u32::MAX } else { r.time() })
+        .collect();
+    times.sort_unstable();
+    let sum: u64 = times[cut_off..cut_off + take]
+        .iter()
+        .map(|t| *t as u64)
+        .sum();
+
+    Some((sum as f32 / take as f32).round() as u32)
+}
+
+#[test]
+fn test_rolling_average() {
+    let records = records(500);
+
+    for scale in [3, 5, 12, 50, 100] {
+        for dnfasok in [false, true] {
+            let stats = rolling_stats(
+                &records,
+                &StatsType::Average(scale),
+                dnfasok,
+                Rounding::Millis,
+            );
+
+            assert!(stats[..scale - 1].iter().all(Option::is_none));
+            for i in scale - 1..records.len() {
+                let expected = naive_average(&records[i + 1 - scale..=i], dnfasok);
+                assert_eq!(stats[i], expected, "ao{} at {}", scale, i);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rolling_mean() {
+    let records = records(100);
+    let stats = rolling_stats(&records, &StatsType::Mean(3), false, Rounding::Millis);
+
+    for i in 2..records.len() {
+        let chunk = &records[i - 2..=i];
+        let expected = if chunk.iter().any(|r| r.solve_state().is_dnf()) {
+            None
+        } else {
+            Some((chunk.iter().map(|r| r.time()).sum::<u32>() as f32 / 3.0).round() as u32)
+        };
+        assert_eq!(stats[i], expected);
+    }
+}
+
+#[test]
+fn test_rolling_median_and_best() {
+    let record = |time, dnf| {
+        let solve_state = if dnf { SolveState::Dnf } else { SolveState::Ok };
+        Rc::new(Record::from(
+            solve_state,
+            time,
+            String::new(),
+            String::new(),
+            0,
+        ))
+    };
+    let records = [
+        record(9000, false),
+        record(7000, true),
+        record(8000, false),
+        record(6000, true),
+        record(5000, true),
+    ];
+
+    let medians = rolling_stats(&records, &StatsType::Median(3), false, Rounding::Millis);
+    assert_eq!(medians, [None, None, Some(9000), None, None]);
+
+    let medians = rolling_stats(&records, &StatsType::Median(4), false, Rounding::Millis);
+    assert_eq!(medians, [None, None, None, None, None]);
+
+    let bests = rolling_stats(&records, &StatsType::BestOf(3), false, Rounding::Millis);
+    assert_eq!(bests, [None, None, Some(8000), Some(8000), Some(8000)]);
+    assert_eq!(
+        rolling_stats(
+            &records[3..],
+            &StatsType::BestOf(2),
+            false,
+            Rounding::Millis
+        ),
+        [None, None]
+    );
+
+    let trimmed = rolling_stats(&records, &StatsType::Trimmed(5, 1), true, Rounding::Millis);
+    assert_eq!(trimmed[4], Some(7000));
+}
+
+#[test]
+fn test_rolling_sd() {
+    let record = |time, dnf| {
+        let solve_state = if dnf { SolveState::Dnf } else { SolveState::Ok };
+        Rc::new(Record::from(
+            solve_state,
+            time,
+            String::new(),
+            String::new(),
+            0,
+        ))
+    };
+    let records = [
+        record(10_000, false),
+        record(12_000, false),
+        record(14_000, false),
+        record(9_000, true),
+        record(14_000, false),
+    ];
+
+    let sds = rolling_sd(&records, 3, false, Rounding::Millis);
+    assert_eq!(sds, [None, None, Some(2000), Some(1414), Some(0)]);
+}
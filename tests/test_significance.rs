@@ -0,0 +1,44 @@
+use cstimer_analyzer_web::significance::*;
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "{} is not close to {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_welch_t_test() {
+    let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let b = [3.0, 4.0, 5.0, 6.0, 7.0];
+    let (t, df, p, (low, high)) = welch_t_test(&a, &b).unwrap();
+
+    assert_close(t, 2.0, 1e-9);
+    assert_close(df, 8.0, 1e-9);
+    assert_close(p, 0.0805, 1e-4);
+    assert_close(low, -0.306, 1e-3);
+    assert_close(high, 4.306, 1e-3);
+
+    assert!(welch_t_test(&a, &[1.0]).is_none());
+    assert!(welch_t_test(&[1.0, 1.0], &[1.0, 1.0]).is_none());
+}
+
+#[test]
+fn test_mann_whitney_u() {
+    let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+    let (u, z, p) = mann_whitney_u(&a, &b).unwrap();
+
+    assert_close(u, 25.0, 1e-9);
+    assert_close(z, 2.5067, 1e-4);
+    assert_close(p, 0.0122, 1e-4);
+
+    // ties are ranked by their average rank
+    let (u, _, p) = mann_whitney_u(&[1.0, 2.0, 2.0], &[2.0, 3.0]).unwrap();
+    assert_close(u, 5.0, 1e-9);
+    assert!(p > 0.05);
+
+    assert!(mann_whitney_u(&[1.0, 1.0], &[1.0]).is_none());
+}
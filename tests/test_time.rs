@@ -23,3 +23,47 @@ fn test_to_readable_string() {
         assert_eq!(millis.to_readable_string(), readable.to_string());
     }
 }
+
+#[test]
+fn test_wca_rounding() {
+    let wca = Rounding::Wca;
+
+    assert_eq!(wca.single(12_349), 12_340);
+    assert_eq!(wca.single(600_499), 600_000);
+    assert_eq!(wca.single(600_500), 601_000);
+
+    // (12.34 + 12.35 + 12.35) / 3 = 12.3466...
+    assert_eq!(wca.mean(37_040, 3), 12_350);
+    // (10.00 + 10.01) / 2 = 10.005, rounded up
+    assert_eq!(wca.mean(20_010, 2), 10_010);
+    assert_eq!(wca.mean(1_801_500, 3), 601_000);
+    assert_eq!(Rounding::Millis.mean(20_001, 2), 10_001);
+    assert_eq!(Rounding::Millis.mean(u32::MAX as u64 * 4, 4), u32::MAX);
+    assert_eq!(Rounding::Millis.mean(0, 0), 0);
+    assert_eq!(wca.mean(0, 0), 0);
+
+    assert_eq!(wca.format(12_350), "12.35");
+    assert_eq!(wca.format(601_000), "10:01.00");
+    assert_eq!(wca.format(2_345), "2.35");
+    assert_eq!(wca.format(2_344), "2.34");
+    assert_eq!(wca.format(59_995), "1:00.00");
+}
+
+#[test]
+fn test_parse_readable() {
+    let pairs = [
+        ("15", Some(15_000)),
+        ("15.00", Some(15_000)),
+        ("9.5", Some(9_500)),
+        ("1:30.00", Some(90_000)),
+        ("1:0:00.000", Some(3_600_000)),
+        ("1:60", None),
+        ("15.0000", None),
+        ("1::00", None),
+        ("x", None),
+    ];
+
+    for (readable, millis) in pairs {
+        assert_eq!(parse_readable(readable), millis);
+    }
+}